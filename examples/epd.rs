@@ -0,0 +1,50 @@
+use plankton::engineoptions::EngineOptions;
+use plankton::epd;
+use plankton::epdposition::EpdPosition;
+use plankton::searchlimits::SearchLimits;
+use plankton::transpositiontable::TranspositionTable;
+use std::env;
+use std::fs;
+use std::process;
+use std::sync::Arc;
+
+// cargo run --release --example epd -- <suite.epd> [depth] [ms per position]
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        eprintln!("usage: {} <suite.epd> [depth] [ms per position]", args[0]);
+        process::exit(2);
+    }
+    let depth = args.get(2).map_or(Ok(u8::MAX), |depth| depth.parse());
+    let search_time = args.get(3).map_or(Ok(5000), |time| time.parse());
+    let (depth, search_time) = match (depth, search_time) {
+        (Ok(depth), Ok(search_time)) => (depth, search_time),
+        _ => {
+            eprintln!("depth and time must be numbers");
+            process::exit(2);
+        }
+    };
+    let suite = fs::read_to_string(&args[1]).unwrap_or_else(|err| {
+        eprintln!("{}: {}", args[1], err);
+        process::exit(2);
+    });
+    let mut positions = Vec::new();
+    for (line_idx, line) in suite.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match EpdPosition::from_epd(line) {
+            Ok(position) => positions.push(position),
+            Err(err) => eprintln!("line {}: {}", line_idx + 1, err),
+        }
+    }
+    let options = EngineOptions::default();
+    let tt = Arc::new(TranspositionTable::new(options.hash_mb));
+    let limits = SearchLimits {
+        depth: Some(depth),
+        move_time: Some(search_time),
+        ..SearchLimits::default()
+    };
+    let report = epd::run_suite(&positions, &limits, &options, &tt);
+    println!("{}", report);
+}
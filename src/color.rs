@@ -0,0 +1,83 @@
+use crate::parseerror::ParseError;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Not;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[repr(u8)]
+pub enum Color {
+    White,
+    Black,
+}
+
+impl Color {
+    pub const ALL: [Color; 2] = [Color::White, Color::Black];
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl Not for Color {
+    type Output = Color;
+    fn not(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+
+impl From<Color> for u8 {
+    fn from(color: Color) -> u8 {
+        color as u8
+    }
+}
+
+impl TryFrom<u8> for Color {
+    type Error = ParseError;
+    fn try_from(index: u8) -> Result<Color, ParseError> {
+        Color::ALL
+            .get(index as usize)
+            .copied()
+            .ok_or_else(|| ParseError::BadColor(index.to_string()))
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Color::White => "white",
+            Color::Black => "black",
+        })
+    }
+}
+
+// takes the FEN letters as well as the full names
+impl FromStr for Color {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Color, ParseError> {
+        match s {
+            "w" | "white" => Ok(Color::White),
+            "b" | "black" => Ok(Color::Black),
+            _ => Err(ParseError::BadColor(s.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_color() {
+        assert_eq!(!Color::White, Color::Black);
+        assert_eq!("b".parse(), Ok(Color::Black));
+        assert_eq!("white".parse(), Ok(Color::White));
+        assert!("x".parse::<Color>().is_err());
+        assert_eq!(Color::try_from(1), Ok(Color::Black));
+        assert!(Color::try_from(2).is_err());
+        assert_eq!(Color::Black.to_string(), "black");
+    }
+}
@@ -0,0 +1,183 @@
+use crate::optionerror::OptionError;
+use crate::piece::Piece;
+use crate::score::Score;
+use std::str::FromStr;
+
+// Everything a UCI setoption can change. The transposition table is owned by the caller, so
+// hash_mb only takes effect when they make a new one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EngineOptions {
+    pub hash_mb: usize,
+    pub threads: usize,
+    // how many best root moves get their own line in the info output
+    pub multi_pv: usize,
+    // ms held back from every time budget for communication lag
+    pub move_overhead: i128,
+    // indexed by piece, the king's has to stay out of reach of the others
+    pub piece_scores: [Score; 6],
+    // skip captures that lose material in quiescence search
+    pub q_see_pruning: bool,
+}
+
+impl Default for EngineOptions {
+    fn default() -> EngineOptions {
+        EngineOptions {
+            hash_mb: 16,
+            threads: num_cpus::get().clamp(1, MAX_THREADS),
+            multi_pv: 1,
+            move_overhead: 10,
+            piece_scores: crate::PIECE_SCORES,
+            q_see_pruning: true,
+        }
+    }
+}
+
+// the table is filled as soon as it's set, so this has to fit in memory on an ordinary machine
+const MAX_HASH_MB: usize = 4096;
+const MAX_THREADS: usize = 256;
+const MAX_MULTI_PV: usize = 256;
+const MAX_MOVE_OVERHEAD: i128 = 5000;
+// a full set of promoted pieces at this value plus the tables still stays clear of the mate scores
+const MAX_PIECE_VALUE: i32 = 1500;
+const VALUED_PIECES: [Piece; 5] = [
+    Piece::Pawn,
+    Piece::Knight,
+    Piece::Bishop,
+    Piece::Rook,
+    Piece::Queen,
+];
+
+impl EngineOptions {
+    // the option lines for the uci command, defaults from the current values
+    pub fn uci_options(&self) -> Vec<String> {
+        let mut options = vec![
+            spin_option("Hash", self.hash_mb, 1, MAX_HASH_MB),
+            spin_option("Threads", self.threads, 1, MAX_THREADS),
+            spin_option("MultiPV", self.multi_pv, 1, MAX_MULTI_PV),
+            spin_option("Move Overhead", self.move_overhead, 0, MAX_MOVE_OVERHEAD),
+        ];
+        for piece in VALUED_PIECES.iter() {
+            options.push(spin_option(
+                &piece_value_name(*piece),
+                self.piece_scores[piece.index()].0,
+                0,
+                MAX_PIECE_VALUE,
+            ));
+        }
+        options.push(format!(
+            "option name QSearch SEE Pruning type check default {}",
+            self.q_see_pruning
+        ));
+        options
+    }
+    // option names are case insensitive in UCI
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), OptionError> {
+        let lowercase_name = name.to_ascii_lowercase();
+        match lowercase_name.as_str() {
+            "hash" => self.hash_mb = parse_spin(name, value, 1, MAX_HASH_MB)?,
+            "threads" => self.threads = parse_spin(name, value, 1, MAX_THREADS)?,
+            "multipv" => self.multi_pv = parse_spin(name, value, 1, MAX_MULTI_PV)?,
+            "move overhead" => self.move_overhead = parse_spin(name, value, 0, MAX_MOVE_OVERHEAD)?,
+            "qsearch see pruning" => {
+                self.q_see_pruning = match value.to_ascii_lowercase().as_str() {
+                    "true" => true,
+                    "false" => false,
+                    _ => return Err(bad_value(name, value)),
+                }
+            }
+            _ => {
+                let piece = VALUED_PIECES
+                    .iter()
+                    .find(|piece| piece_value_name(**piece).to_ascii_lowercase() == lowercase_name)
+                    .ok_or_else(|| OptionError::UnknownOption(name.to_owned()))?;
+                self.piece_scores[piece.index()] =
+                    Score(parse_spin(name, value, 0, MAX_PIECE_VALUE)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn piece_value_name(piece: Piece) -> String {
+    let name = match piece {
+        Piece::Pawn => "Pawn",
+        Piece::Knight => "Knight",
+        Piece::Bishop => "Bishop",
+        Piece::Rook => "Rook",
+        Piece::Queen => "Queen",
+        Piece::King => "King",
+    };
+    format!("{} Value", name)
+}
+
+fn spin_option<T: ToString>(name: &str, default: T, min: T, max: T) -> String {
+    format!(
+        "option name {} type spin default {} min {} max {}",
+        name,
+        default.to_string(),
+        min.to_string(),
+        max.to_string()
+    )
+}
+
+fn parse_spin<T: FromStr + PartialOrd>(
+    name: &str,
+    value: &str,
+    min: T,
+    max: T,
+) -> Result<T, OptionError> {
+    match value.parse() {
+        Ok(parsed) if parsed >= min && parsed <= max => Ok(parsed),
+        _ => Err(bad_value(name, value)),
+    }
+}
+
+fn bad_value(name: &str, value: &str) -> OptionError {
+    OptionError::BadValue {
+        name: name.to_owned(),
+        value: value.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engineoptions::EngineOptions;
+    use crate::optionerror::OptionError;
+    use crate::score::Score;
+
+    #[test]
+    fn test_set_option() {
+        let mut options = EngineOptions::default();
+        // a gui echoing the advertised default back has to get it accepted
+        let threads = options.threads.to_string();
+        assert_eq!(options.set("Threads", &threads), Ok(()));
+        options.set("Threads", "1").unwrap();
+        options.set("hash", "64").unwrap();
+        options.set("MultiPV", "3").unwrap();
+        options.set("Move Overhead", "0").unwrap();
+        options.set("Knight Value", "310").unwrap();
+        options.set("QSearch SEE Pruning", "false").unwrap();
+        assert_eq!(
+            (options.threads, options.hash_mb, options.multi_pv),
+            (1, 64, 3)
+        );
+        assert_eq!(options.move_overhead, 0);
+        assert_eq!(options.piece_scores[1], Score(310));
+        assert!(!options.q_see_pruning);
+        assert!(options
+            .uci_options()
+            .contains(&"option name Knight Value type spin default 310 min 0 max 1500".to_owned()));
+        assert_eq!(
+            options.set("Threads", "0"),
+            Err(OptionError::BadValue {
+                name: "Threads".to_owned(),
+                value: "0".to_owned()
+            })
+        );
+        assert_eq!(
+            options.set("King Value", "1"),
+            Err(OptionError::UnknownOption("King Value".to_owned()))
+        );
+        assert_eq!(options.threads, 1);
+    }
+}
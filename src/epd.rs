@@ -0,0 +1,260 @@
+use crate::engineoptions::EngineOptions;
+use crate::epderror::EpdError;
+use crate::epdposition::EpdPosition;
+use crate::epdreport::EpdReport;
+use crate::epdresult::EpdResult;
+use crate::game::Game;
+use crate::piecemove::PieceMove;
+use crate::search;
+use crate::searchlimits::SearchLimits;
+use crate::transpositiontable::TranspositionTable;
+use std::sync::Arc;
+
+impl EpdPosition {
+    // the four FEN fields without clocks, then operations like bm Qg6; id "WAC.001";
+    pub fn from_epd(epd: &str) -> Result<EpdPosition, EpdError> {
+        let epd = epd.trim();
+        let mut fen_end = 0;
+        for _ in 0..4 {
+            let field_start = fen_end + epd[fen_end..].len() - epd[fen_end..].trim_start().len();
+            fen_end = epd[field_start..]
+                .find(char::is_whitespace)
+                .map_or(epd.len(), |field_len| field_start + field_len);
+        }
+        let mut game = Game::from_fen(&epd[..fen_end]).map_err(EpdError::BadFen)?;
+        let mut epd_position = EpdPosition {
+            game: game.clone(),
+            best_moves: Vec::new(),
+            avoid_moves: Vec::new(),
+            id: None,
+            comment: None,
+            operations: parse_operations(&epd[fen_end..])?,
+        };
+        for (opcode, operands) in &epd_position.operations {
+            let bad_operation =
+                || EpdError::BadOperation(format!("{} {}", opcode, operands.join(" ")));
+            match opcode.as_str() {
+                "bm" | "am" => {
+                    for san in operands {
+                        let piece_move =
+                            game.parse_san_move(san)
+                                .map_err(|error| EpdError::BadMove {
+                                    opcode: opcode.clone(),
+                                    error,
+                                })?;
+                        if opcode == "bm" {
+                            epd_position.best_moves.push(piece_move);
+                        } else {
+                            epd_position.avoid_moves.push(piece_move);
+                        }
+                    }
+                }
+                "id" => epd_position.id = operands.first().cloned(),
+                "c0" => epd_position.comment = operands.first().cloned(),
+                "hmvc" => {
+                    let halfmove_clock = operands.first().and_then(|clock| clock.parse().ok());
+                    epd_position.game.halfmove_clock = halfmove_clock.ok_or_else(bad_operation)?;
+                }
+                "fmvn" => {
+                    let fullmove_number = operands.first().and_then(|number| number.parse().ok());
+                    epd_position.game.fullmove_number =
+                        fullmove_number.ok_or_else(bad_operation)?;
+                }
+                _ => (),
+            }
+        }
+        Ok(epd_position)
+    }
+    pub fn passes(&self, piece_move: &PieceMove) -> bool {
+        (self.best_moves.is_empty() || self.best_moves.contains(piece_move))
+            && !self.avoid_moves.contains(piece_move)
+    }
+    pub fn run(
+        &self,
+        limits: &SearchLimits,
+        options: &EngineOptions,
+        tt: &Arc<TranspositionTable>,
+    ) -> EpdResult {
+        let mut game = self.game.clone();
+        let found = search::search(&mut game, limits, options, tt).map(|result| result.best_move);
+        let mut expected = Vec::new();
+        for (opcode, moves) in &[("bm", &self.best_moves), ("am", &self.avoid_moves)] {
+            if !moves.is_empty() {
+                let sans: Vec<String> = moves
+                    .iter()
+                    .map(|piece_move| game.move_to_san(piece_move))
+                    .collect();
+                expected.push(format!("{} {}", opcode, sans.join(" ")));
+            }
+        }
+        EpdResult {
+            id: self.id.clone().unwrap_or_else(|| "?".to_owned()),
+            passed: found.is_some_and(|piece_move| self.passes(&piece_move)),
+            found,
+            found_san: found.map_or("nothing".to_owned(), |piece_move| {
+                game.move_to_san(&piece_move)
+            }),
+            expected: expected.join(", "),
+        }
+    }
+}
+
+// every position is searched from an empty transposition table so results don't depend on order
+pub fn run_suite(
+    positions: &[EpdPosition],
+    limits: &SearchLimits,
+    options: &EngineOptions,
+    tt: &Arc<TranspositionTable>,
+) -> EpdReport {
+    let mut report = EpdReport::default();
+    for (position_idx, position) in positions.iter().enumerate() {
+        tt.clear();
+        let mut result = position.run(limits, options, tt);
+        if position.id.is_none() {
+            result.id = format!("#{}", position_idx + 1);
+        }
+        report.results.push(result);
+    }
+    report
+}
+
+// opcode followed by operands up to a semicolon, quoted operands can hold spaces and semicolons
+fn parse_operations(operations: &str) -> Result<Vec<(String, Vec<String>)>, EpdError> {
+    let mut parsed = Vec::new();
+    let mut tokens: Vec<String> = Vec::new();
+    let mut chars = operations.chars();
+    let mut token = String::new();
+    let mut finish_operation = |tokens: &mut Vec<String>| -> Result<(), EpdError> {
+        if tokens.is_empty() {
+            return Ok(());
+        }
+        let opcode = tokens.remove(0);
+        let valid_opcode = opcode.starts_with(|c: char| c.is_ascii_alphabetic())
+            && opcode
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_opcode {
+            return Err(EpdError::BadOperation(opcode));
+        }
+        parsed.push((opcode, tokens.split_off(0)));
+        Ok(())
+    };
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                let quoted: String = chars.by_ref().take_while(|c| *c != '"').collect();
+                tokens.push(quoted);
+            }
+            ';' => {
+                if !token.is_empty() {
+                    tokens.push(token.split_off(0));
+                }
+                finish_operation(&mut tokens)?;
+            }
+            c if c.is_whitespace() => {
+                if !token.is_empty() {
+                    tokens.push(token.split_off(0));
+                }
+            }
+            c => token.push(c),
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    // the last semicolon is sometimes left off
+    finish_operation(&mut tokens)?;
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engineoptions::EngineOptions;
+    use crate::epderror::EpdError;
+    use crate::epdposition::EpdPosition;
+    use crate::fenerror::FenError;
+    use crate::moveerror::MoveError;
+    use crate::searchlimits::SearchLimits;
+    use crate::transpositiontable::TranspositionTable;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_from_epd() {
+        let epd_position = EpdPosition::from_epd(
+            "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";",
+        )
+        .unwrap();
+        assert_eq!(epd_position.id.as_deref(), Some("WAC.001"));
+        let mut game = epd_position.game.clone();
+        assert_eq!(
+            epd_position.best_moves,
+            vec![game.parse_uci_move("g3g6").unwrap()]
+        );
+        assert!(epd_position.avoid_moves.is_empty());
+
+        let epd_position = EpdPosition::from_epd(
+            "r1b1k2r/ppppnppp/2n2q2/2b5/3NP3/2P1B3/PP3PPP/RN1QKB1R w KQkq - \
+             am Nxc6 Bg5; bm Nb5 Qd2;c0 \"two; \\ moves\"; hmvc 3; fmvn 7; xyz",
+        )
+        .unwrap();
+        assert_eq!(epd_position.best_moves.len(), 2);
+        assert_eq!(epd_position.avoid_moves.len(), 2);
+        assert_eq!(epd_position.comment.as_deref(), Some("two; \\ moves"));
+        assert_eq!(
+            epd_position.game.to_fen(),
+            "r1b1k2r/ppppnppp/2n2q2/2b5/3NP3/2P1B3/PP3PPP/RN1QKB1R w KQkq - 3 7"
+        );
+        assert_eq!(epd_position.operations.len(), 6);
+        assert_eq!(epd_position.operations[5], ("xyz".to_owned(), Vec::new()));
+    }
+
+    #[test]
+    fn test_epd_errors() {
+        assert!(matches!(
+            EpdPosition::from_epd("8/8/8/8 w - - bm e4;"),
+            Err(EpdError::BadFen(FenError::WrongRankCount(4)))
+        ));
+        assert_eq!(
+            EpdPosition::from_epd("4k3/8/8/8/8/8/8/4K3 w - - bm Qh5;").err(),
+            Some(EpdError::BadMove {
+                opcode: "bm".to_owned(),
+                error: MoveError::Illegal("Qh5".to_owned())
+            })
+        );
+        assert_eq!(
+            EpdPosition::from_epd("4k3/8/8/8/8/8/8/4K3 w - - 1x 2;").err(),
+            Some(EpdError::BadOperation("1x".to_owned()))
+        );
+        assert_eq!(
+            EpdPosition::from_epd("4k3/8/8/8/8/8/8/4K3 w - - hmvc x;").err(),
+            Some(EpdError::BadOperation("hmvc x".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_run_suite() {
+        let positions: Vec<EpdPosition> = [
+            "6k1/5ppp/8/8/8/8/8/1K2R3 w - - bm Re8#; id \"mate\";",
+            "6k1/5ppp/8/8/8/8/8/1K2R3 w - - am Re8#;",
+        ]
+        .iter()
+        .map(|epd| EpdPosition::from_epd(epd).unwrap())
+        .collect();
+        let report = crate::epd::run_suite(
+            &positions,
+            &SearchLimits {
+                depth: Some(2),
+                ..SearchLimits::default()
+            },
+            &EngineOptions::default(),
+            &Arc::new(TranspositionTable::new(1)),
+        );
+        assert_eq!(report.passed(), 1);
+        assert!(report.results[0].passed);
+        assert_eq!(report.results[1].id, "#2");
+        assert_eq!(
+            report.to_string(),
+            "mate: pass (played Re8#, bm Re8#)\n#2: fail (played Re8#, am Re8#)\nscore: 1/2"
+        );
+    }
+}
@@ -0,0 +1,25 @@
+use crate::fenerror::FenError;
+use crate::moveerror::MoveError;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EpdError {
+    BadFen(FenError),
+    BadOperation(String),
+    BadMove { opcode: String, error: MoveError },
+}
+
+impl fmt::Display for EpdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EpdError::BadFen(err) => write!(f, "position: {}", err),
+            EpdError::BadOperation(operation) => {
+                write!(f, "malformed operation \"{}\"", operation)
+            }
+            EpdError::BadMove { opcode, error } => write!(f, "{}: {}", opcode, error),
+        }
+    }
+}
+
+impl Error for EpdError {}
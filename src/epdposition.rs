@@ -0,0 +1,15 @@
+use crate::game::Game;
+use crate::piecemove::PieceMove;
+
+#[derive(Clone)]
+pub struct EpdPosition {
+    pub game: Game,
+    // bm, any of them is a pass
+    pub best_moves: Vec<PieceMove>,
+    // am, none of them may be played
+    pub avoid_moves: Vec<PieceMove>,
+    pub id: Option<String>,
+    pub comment: Option<String>,
+    // every operation as written, including the ones above and any this crate doesn't use
+    pub operations: Vec<(String, Vec<String>)>,
+}
@@ -0,0 +1,22 @@
+use crate::epdresult::EpdResult;
+use std::fmt;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EpdReport {
+    pub results: Vec<EpdResult>,
+}
+
+impl EpdReport {
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|result| result.passed).count()
+    }
+}
+
+impl fmt::Display for EpdReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for result in &self.results {
+            writeln!(f, "{}", result)?;
+        }
+        write!(f, "score: {}/{}", self.passed(), self.results.len())
+    }
+}
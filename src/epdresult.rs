@@ -0,0 +1,25 @@
+use crate::piecemove::PieceMove;
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EpdResult {
+    pub id: String,
+    pub passed: bool,
+    pub found: Option<PieceMove>,
+    // SAN of the found move, and of the expected moves as in "bm Qg6" or "am Bxh7"
+    pub found_san: String,
+    pub expected: String,
+}
+
+impl fmt::Display for EpdResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} (played {}, {})",
+            self.id,
+            if self.passed { "pass" } else { "fail" },
+            self.found_san,
+            self.expected
+        )
+    }
+}
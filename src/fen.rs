@@ -0,0 +1,332 @@
+use crate::color::Color;
+use crate::fenerror::FenError;
+use crate::game::Game;
+use crate::piece::Piece;
+use crate::square::Square;
+
+// same order as castle_available
+const CASTLE_CHARS: [char; 4] = ['K', 'Q', 'k', 'q'];
+const CASTLE_ROOK_SQUARES: [Square; 4] = [Square::H1, Square::A1, Square::H8, Square::A8];
+
+impl Game {
+    pub fn from_fen(fen: &str) -> Result<Game, FenError> {
+        let mut fields = fen.split_whitespace();
+        let placement = fields
+            .next()
+            .ok_or(FenError::MissingField("piece placement"))?;
+        let side_to_move = fields
+            .next()
+            .ok_or(FenError::MissingField("side to move"))?;
+        let castling = fields.next().ok_or(FenError::MissingField("castling"))?;
+        let en_passant = fields.next().ok_or(FenError::MissingField("en passant"))?;
+        // plenty of FENs leave the clocks off
+        let halfmove_clock = fields.next();
+        let fullmove_number = fields.next();
+        if let Some(extra) = fields.next() {
+            return Err(FenError::ExtraField(extra.to_owned()));
+        }
+
+        let mut game = Game::new();
+        let ranks: Vec<&str> = placement.split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenError::WrongRankCount(ranks.len()));
+        }
+        for (rank_idx, rank_str) in ranks.iter().enumerate() {
+            let rank = 7 - rank_idx as u8;
+            let mut length = 0;
+            for piece_char in rank_str.chars() {
+                match piece_char.to_digit(10) {
+                    Some(empty @ 1..=8) => length += empty,
+                    _ => {
+                        let piece = Piece::from_char(piece_char).ok_or(FenError::BadPieceChar {
+                            rank: rank + 1,
+                            piece_char,
+                        })?;
+                        if length < 8 {
+                            let color = if piece_char.is_ascii_uppercase() {
+                                Color::White
+                            } else {
+                                Color::Black
+                            };
+                            game.create_piece(
+                                color,
+                                piece,
+                                Square::from_coords(length as u8, rank),
+                            );
+                        }
+                        length += 1;
+                    }
+                }
+            }
+            if length != 8 {
+                return Err(FenError::WrongRankLength {
+                    rank: rank + 1,
+                    length,
+                });
+            }
+        }
+        for color in Color::ALL.iter().copied() {
+            let count = game.piece_positions[color as usize][Piece::King as usize].count_ones();
+            if count != 1 {
+                return Err(FenError::KingCount { color, count });
+            }
+        }
+        let back_rank_pawns = (game.piece_positions[Color::White as usize][Piece::Pawn as usize]
+            | game.piece_positions[Color::Black as usize][Piece::Pawn as usize])
+            & 0xff000000000000ff;
+        if back_rank_pawns != 0 {
+            return Err(FenError::PawnOnBackRank(Square::new(
+                back_rank_pawns.trailing_zeros() as u8,
+            )));
+        }
+
+        let color = match side_to_move {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return Err(FenError::BadSideToMove(side_to_move.to_owned())),
+        };
+        game.side_to_move = color;
+
+        if castling != "-" {
+            for castle_char in castling.chars() {
+                let castle_idx = CASTLE_CHARS
+                    .iter()
+                    .position(|other| *other == castle_char)
+                    .ok_or_else(|| {
+                        FenError::BadCastling(format!("unknown character '{}'", castle_char))
+                    })?;
+                if game.castle_available[castle_idx] {
+                    return Err(FenError::BadCastling(format!(
+                        "'{}' given twice",
+                        castle_char
+                    )));
+                }
+                let castle_color = Color::ALL[castle_idx / 2];
+                let king_square = if castle_color == Color::White {
+                    Square::E1
+                } else {
+                    Square::E8
+                };
+                let rook_square = CASTLE_ROOK_SQUARES[castle_idx];
+                if game.piece_at(king_square) != Some((castle_color, Piece::King))
+                    || game.piece_at(rook_square) != Some((castle_color, Piece::Rook))
+                {
+                    return Err(FenError::BadCastling(format!(
+                        "'{}' without the king and rook on their starting squares",
+                        castle_char
+                    )));
+                }
+                game.castle_available[castle_idx] = true;
+            }
+        }
+
+        if en_passant != "-" {
+            let square: Square = en_passant.parse().map_err(|_| {
+                FenError::BadEnPassant(format!("\"{}\" is not a square", en_passant))
+            })?;
+            let target_rank = if color == Color::White { 5 } else { 2 };
+            if square.rank() != target_rank {
+                return Err(FenError::BadEnPassant(format!(
+                    "{} is on the wrong rank for {} to move",
+                    en_passant, color
+                )));
+            }
+            // the pawn that just moved two squares is in front of the target square
+            let (pawn_square, start_square) = if color == Color::White {
+                (square.index() - 8, square.index() + 8)
+            } else {
+                (square.index() + 8, square.index() - 8)
+            };
+            if game.piece_positions[!color as usize][Piece::Pawn as usize] & (1u64 << pawn_square)
+                == 0
+                || game.square_exists[square.index()]
+                || game.square_exists[start_square]
+            {
+                return Err(FenError::BadEnPassant(format!(
+                    "no pawn could have just passed {}",
+                    en_passant
+                )));
+            }
+            game.en_passant = Some(square);
+        }
+
+        if let Some(halfmove_clock) = halfmove_clock {
+            game.halfmove_clock = halfmove_clock
+                .parse()
+                .map_err(|_| FenError::BadHalfmoveClock(halfmove_clock.to_owned()))?;
+        }
+        if let Some(fullmove_number) = fullmove_number {
+            game.fullmove_number = match fullmove_number.parse() {
+                Ok(number) if number > 0 => number,
+                _ => return Err(FenError::BadFullmoveNumber(fullmove_number.to_owned())),
+            };
+        }
+
+        game.hash = game.compute_hash();
+        game.set_moves();
+        if game.in_check(!color) {
+            return Err(FenError::OpponentInCheck);
+        }
+        Ok(game)
+    }
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                let square = rank * 8 + file;
+                if !self.square_exists[square] {
+                    empty += 1;
+                    continue;
+                }
+                if empty != 0 {
+                    fen.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                let piece_char = self.square_to_piece[square].to_char();
+                if self.square_to_color[square] == Color::White {
+                    fen.push(piece_char);
+                } else {
+                    fen.push(piece_char.to_ascii_lowercase());
+                }
+            }
+            if empty != 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank != 0 {
+                fen.push('/');
+            }
+        }
+        fen.push_str(if self.side_to_move == Color::White {
+            " w "
+        } else {
+            " b "
+        });
+        if self.castle_available.iter().any(|available| *available) {
+            for (castle_idx, castle_char) in CASTLE_CHARS.iter().enumerate() {
+                if self.castle_available[castle_idx] {
+                    fen.push(*castle_char);
+                }
+            }
+        } else {
+            fen.push('-');
+        }
+        match self.en_passant {
+            Some(square) => fen.push_str(&format!(" {}", square)),
+            None => fen.push_str(" -"),
+        }
+        fen.push_str(&format!(
+            " {} {}",
+            self.halfmove_clock, self.fullmove_number
+        ));
+        fen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+    use crate::fenerror::FenError;
+    use crate::game::Game;
+    use crate::square::Square;
+
+    #[test]
+    fn test_from_fen() {
+        let mut start = Game::new();
+        start.starting_game();
+        let game =
+            Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        assert!(game == start);
+
+        let game =
+            Game::from_fen("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b Kq e3 0 3").unwrap();
+        assert_eq!(game.side_to_move, Color::Black);
+        assert_eq!(game.en_passant, Some(Square::E3));
+        assert_eq!(game.castle_available, [true, false, false, true]);
+        assert_eq!((game.halfmove_clock, game.fullmove_number), (0, 3));
+        assert_eq!(game.hash, game.compute_hash());
+
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - -").unwrap();
+        assert_eq!((game.halfmove_clock, game.fullmove_number), (0, 1));
+    }
+    #[test]
+    fn test_to_fen() {
+        let mut game = Game::new();
+        game.starting_game();
+        assert_eq!(
+            game.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+        );
+        for fen in &[
+            "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b Kq e3 0 3",
+            "8/8/4k3/8/2p5/8/B2P2K1/8 w - - 17 42",
+        ] {
+            let game = Game::from_fen(fen).unwrap();
+            assert_eq!(game.to_fen(), *fen);
+        }
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/4K3 b - -").unwrap();
+        assert_eq!(game.to_fen(), "4k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    }
+    #[test]
+    fn test_fen_errors() {
+        let error = |fen| Game::from_fen(fen).err().unwrap();
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3"),
+            FenError::MissingField("side to move")
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/4K3 w - -"),
+            FenError::WrongRankCount(7)
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K2X w - -"),
+            FenError::BadPieceChar {
+                rank: 1,
+                piece_char: 'X'
+            }
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K4 w - -"),
+            FenError::WrongRankLength { rank: 1, length: 9 }
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/3KK3 w - -"),
+            FenError::KingCount {
+                color: Color::White,
+                count: 2
+            }
+        );
+        assert_eq!(
+            error("4k2p/8/8/8/8/8/8/4K3 w - -"),
+            FenError::PawnOnBackRank(Square::H8)
+        );
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4K3 x - -"),
+            FenError::BadSideToMove("x".to_owned())
+        );
+        assert!(matches!(
+            error("4k3/8/8/8/8/8/8/4K3 w K -"),
+            FenError::BadCastling(_)
+        ));
+        assert!(matches!(
+            error("4k3/8/8/8/8/8/8/4K3 w - e3"),
+            FenError::BadEnPassant(_)
+        ));
+        assert!(matches!(
+            error("4k3/8/8/8/8/8/8/4K3 w - - x 1"),
+            FenError::BadHalfmoveClock(_)
+        ));
+        assert!(matches!(
+            error("4k3/8/8/8/8/8/8/4K3 w - - 0 0"),
+            FenError::BadFullmoveNumber(_)
+        ));
+        assert_eq!(
+            error("4k3/8/8/8/8/8/8/4R1K1 w - -"),
+            FenError::OpponentInCheck
+        );
+        assert_eq!(
+            error("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 moves"),
+            FenError::ExtraField("moves".to_owned())
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use crate::color::Color;
+use crate::square::Square;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FenError {
+    MissingField(&'static str),
+    ExtraField(String),
+    WrongRankCount(usize),
+    BadPieceChar { rank: u8, piece_char: char },
+    WrongRankLength { rank: u8, length: u32 },
+    KingCount { color: Color, count: u32 },
+    PawnOnBackRank(Square),
+    BadSideToMove(String),
+    BadCastling(String),
+    BadEnPassant(String),
+    BadHalfmoveClock(String),
+    BadFullmoveNumber(String),
+    OpponentInCheck,
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::MissingField(field) => write!(f, "missing {} field", field),
+            FenError::ExtraField(field) => write!(f, "unexpected extra field \"{}\"", field),
+            FenError::WrongRankCount(count) => {
+                write!(f, "piece placement: {} ranks instead of 8", count)
+            }
+            FenError::BadPieceChar { rank, piece_char } => write!(
+                f,
+                "piece placement: bad piece character '{}' on rank {}",
+                piece_char, rank
+            ),
+            FenError::WrongRankLength { rank, length } => write!(
+                f,
+                "piece placement: rank {} has {} squares instead of 8",
+                rank, length
+            ),
+            FenError::KingCount { color, count } => {
+                write!(f, "piece placement: {} {} kings instead of 1", count, color)
+            }
+            FenError::PawnOnBackRank(square) => write!(f, "piece placement: pawn on {}", square),
+            FenError::BadSideToMove(field) => {
+                write!(f, "side to move: expected w or b, got \"{}\"", field)
+            }
+            FenError::BadCastling(reason) => write!(f, "castling: {}", reason),
+            FenError::BadEnPassant(reason) => write!(f, "en passant: {}", reason),
+            FenError::BadHalfmoveClock(field) => {
+                write!(f, "halfmove clock: expected a number, got \"{}\"", field)
+            }
+            FenError::BadFullmoveNumber(field) => write!(
+                f,
+                "fullmove number: expected a number above 0, got \"{}\"",
+                field
+            ),
+            FenError::OpponentInCheck => write!(f, "the side not to move is in check"),
+        }
+    }
+}
+
+impl Error for FenError {}
@@ -1,37 +1,80 @@
+use crate::color::Color;
 use crate::movegen::MoveGen;
 use crate::moveutil;
+use crate::piece::Piece;
 use crate::piecemove::PieceMove;
 use crate::prevgamestate::PrevGameState;
 use crate::specialmove::SpecialMove;
+use crate::square::Square;
+use crate::zobrist;
 use tinyvec::ArrayVec;
 
 #[derive(Clone, PartialEq)]
 pub struct Game {
     pub piece_positions: [[u64; 6]; 2],
-    pub square_to_color: [u8; 64],
-    pub square_to_piece: [u8; 64],
+    pub square_to_color: [Color; 64],
+    pub square_to_piece: [Piece; 64],
     pub square_exists: [bool; 64],
     pub square_moves: [ArrayVec<[PieceMove; 28]>; 64],
     pub castle_available: [bool; 4],
+    pub side_to_move: Color,
+    pub en_passant: Option<Square>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+    pub hash: u64,
+    pub hash_history: Vec<u64>,
     move_gen: MoveGen,
 }
 
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Game {
     pub fn new() -> Game {
         Game {
             piece_positions: [[0; 6]; 2],
-            square_to_color: [0; 64],
-            square_to_piece: [0; 64],
+            square_to_color: [Color::White; 64],
+            square_to_piece: [Piece::Pawn; 64],
             square_exists: [false; 64],
             square_moves: [ArrayVec::new(); 64],
             castle_available: [false; 4],
+            side_to_move: Color::White,
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            hash: 0,
+            hash_history: Vec::new(),
             move_gen: MoveGen::new(),
         }
     }
-    pub fn game_over(&mut self, color: u8) -> bool {
-        self.in_checkmate(color) || self.in_stalemate(color)
+    pub fn game_over(&mut self) -> bool {
+        let color = self.side_to_move;
+        self.in_checkmate(color)
+            || self.in_stalemate(color)
+            || self.fifty_move_draw()
+            || self.threefold_repetition()
+    }
+    pub fn fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+    pub fn threefold_repetition(&self) -> bool {
+        self.repetition_count() >= 2
     }
-    pub fn in_checkmate(&mut self, color: u8) -> bool {
+    // times the current position came up before, only looking back to the last pawn move or capture
+    pub fn repetition_count(&self) -> usize {
+        self.hash_history
+            .iter()
+            .rev()
+            .take(self.halfmove_clock as usize)
+            .skip(1)
+            .step_by(2)
+            .filter(|&&hash| hash == self.hash)
+            .count()
+    }
+    pub fn in_checkmate(&mut self, color: Color) -> bool {
         if !self.in_check(color) {
             return false;
         }
@@ -41,7 +84,7 @@ impl Game {
             }
             let piece = self.square_to_piece[square];
             for move_idx in 0..self.square_moves[square].len() {
-                let piece_move = self.square_moves[square][move_idx as usize];
+                let piece_move = self.square_moves[square][move_idx];
                 if !moveutil::legal_move(self, color, piece, &piece_move) {
                     continue;
                 }
@@ -55,7 +98,7 @@ impl Game {
         }
         true
     }
-    pub fn in_stalemate(&mut self, color: u8) -> bool {
+    pub fn in_stalemate(&mut self, color: Color) -> bool {
         if self.in_check(color) {
             return false;
         }
@@ -65,7 +108,7 @@ impl Game {
             }
             let piece = self.square_to_piece[square];
             for move_idx in 0..self.square_moves[square].len() {
-                let piece_move = self.square_moves[square][move_idx as usize];
+                let piece_move = self.square_moves[square][move_idx];
                 if moveutil::legal_move(self, color, piece, &piece_move) {
                     return false;
                 }
@@ -73,13 +116,13 @@ impl Game {
         }
         true
     }
-    pub fn in_check(&self, color: u8) -> bool {
-        let opponent_color = color ^ 1;
+    pub fn in_check(&self, color: Color) -> bool {
+        let opponent_color = !color;
         for square in 0..64 {
             if !self.square_exists[square] || self.square_to_color[square] != opponent_color {
                 continue;
             }
-            if self.piece_positions[color as usize][5]
+            if self.piece_positions[color as usize][Piece::King as usize]
                 & moveutil::piecemoves_to_bitboard(self.square_moves[square])
                 != 0
             {
@@ -88,229 +131,214 @@ impl Game {
         }
         false
     }
-    pub fn make_move(&mut self, color: u8, piece: u8, piece_move: &PieceMove) -> PrevGameState {
-        self.square_exists[piece_move.start as usize] = false;
-        self.square_to_color[piece_move.start as usize] = 0;
-        self.square_to_piece[piece_move.start as usize] = 0;
-        let opponent_color = color ^ 1;
-        let mut capture_piece = 0;
+    pub fn legal_moves(&mut self) -> Vec<PieceMove> {
+        let color = self.side_to_move;
+        let mut moves = Vec::new();
+        for square in 0..64 {
+            if !self.square_exists[square] || self.square_to_color[square] != color {
+                continue;
+            }
+            let piece = self.square_to_piece[square];
+            for move_idx in 0..self.square_moves[square].len() {
+                let piece_move = self.square_moves[square][move_idx];
+                if moveutil::legal_move(self, color, piece, &piece_move) {
+                    moves.push(piece_move);
+                }
+            }
+        }
+        moves
+    }
+    // make_move for the side to move
+    pub fn play_move(&mut self, piece_move: &PieceMove) -> PrevGameState {
+        let piece = self.square_to_piece[piece_move.start.index()];
+        self.make_move(self.side_to_move, piece, piece_move)
+    }
+    // unmake_move for the last move played with play_move
+    pub fn undo_move(&mut self, piece_move: &PieceMove, prev_game_state: &PrevGameState) {
+        let piece = if piece_move.special.promotion_piece().is_some() {
+            Piece::Pawn
+        } else {
+            self.square_to_piece[piece_move.end.index()]
+        };
+        self.unmake_move(!self.side_to_move, piece, piece_move, prev_game_state);
+    }
+    pub fn make_move(
+        &mut self,
+        color: Color,
+        piece: Piece,
+        piece_move: &PieceMove,
+    ) -> PrevGameState {
+        let prev_castle_available = self.castle_available;
+        let prev_hash = self.hash;
+        self.hash_history.push(prev_hash);
+        let start = piece_move.start.index();
+        let end = piece_move.end.index();
+        self.clear_square(start);
+        let opponent_color = !color;
+        let mut capture_piece = Piece::Pawn;
         let mut is_capture = false;
-        if self.square_exists[piece_move.end as usize] {
-            capture_piece = self.square_to_piece[piece_move.end as usize];
+        if self.square_exists[end] {
+            capture_piece = self.square_to_piece[end];
             is_capture = true;
-            self.piece_positions[opponent_color as usize][capture_piece as usize] &=
-                !(1u64 << piece_move.end);
+            self.piece_positions[opponent_color as usize][capture_piece as usize] &= !(1u64 << end);
+            self.hash ^= zobrist::piece_key(opponent_color, capture_piece, piece_move.end);
+            // a rook taken on its starting square can't castle anymore
+            match end {
+                0 => self.castle_available[1] = false,
+                7 => self.castle_available[0] = false,
+                56 => self.castle_available[3] = false,
+                63 => self.castle_available[2] = false,
+                _ => (),
+            }
         }
-        self.square_exists[piece_move.end as usize] = true;
-        self.square_to_color[piece_move.end as usize] = color;
-        self.square_to_piece[piece_move.end as usize] = piece;
-        self.piece_positions[color as usize][piece as usize] &= !(1u64 << piece_move.start);
-        self.piece_positions[color as usize][piece as usize] |= 1u64 << piece_move.end;
-        let prev_castle_available = self.castle_available;
-        if piece == 5 {
-            self.castle_available = [false; 4];
-            if piece_move.special == SpecialMove::CastleKingside {
-                self.piece_positions[color as usize][3] &= !(1u64 << (piece_move.end + 1));
-                self.piece_positions[color as usize][3] |= 1u64 << (piece_move.end - 1);
-                self.square_exists[piece_move.end as usize + 1] = false;
-                self.square_to_color[piece_move.end as usize + 1] = 0;
-                self.square_to_piece[piece_move.end as usize + 1] = 0;
-                self.square_exists[piece_move.end as usize - 1] = true;
-                self.square_to_color[piece_move.end as usize - 1] = color;
-                self.square_to_piece[piece_move.end as usize - 1] = 3;
-            } else if piece_move.special == SpecialMove::CastleQueenside {
-                self.piece_positions[color as usize][3] &= !(1u64 << (piece_move.end - 2));
-                self.piece_positions[color as usize][3] |= 1u64 << (piece_move.end + 1);
-                self.square_exists[piece_move.end as usize - 2] = false;
-                self.square_to_color[piece_move.end as usize - 2] = 0;
-                self.square_to_piece[piece_move.end as usize - 2] = 0;
-                self.square_exists[piece_move.end as usize + 1] = true;
-                self.square_to_color[piece_move.end as usize + 1] = color;
-                self.square_to_piece[piece_move.end as usize + 1] = 3;
+        self.fill_square(end, color, piece);
+        self.piece_positions[color as usize][piece as usize] &= !(1u64 << start);
+        self.piece_positions[color as usize][piece as usize] |= 1u64 << end;
+        self.hash ^= zobrist::piece_key(color, piece, piece_move.start)
+            ^ zobrist::piece_key(color, piece, piece_move.end);
+        let prev_en_passant = self.en_passant;
+        self.en_passant = None;
+        let prev_halfmove_clock = self.halfmove_clock;
+        let prev_fullmove_number = self.fullmove_number;
+        if piece == Piece::Pawn || is_capture {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if color == Color::Black {
+            self.fullmove_number += 1;
+        }
+        self.side_to_move = opponent_color;
+        if piece == Piece::King {
+            self.castle_available[color as usize * 2] = false;
+            self.castle_available[color as usize * 2 + 1] = false;
+            if let Some((rook_start, rook_end)) = Game::castle_rook_squares(piece_move) {
+                self.piece_positions[color as usize][Piece::Rook as usize] &= !(1u64 << rook_start);
+                self.piece_positions[color as usize][Piece::Rook as usize] |= 1u64 << rook_end;
+                self.hash ^= zobrist::piece_key(color, Piece::Rook, Square::new(rook_start as u8))
+                    ^ zobrist::piece_key(color, Piece::Rook, Square::new(rook_end as u8));
+                self.clear_square(rook_start);
+                self.fill_square(rook_end, color, Piece::Rook);
             }
-        } else if piece == 3 {
-            match piece_move.start {
+        } else if piece == Piece::Rook {
+            match start {
                 0 => self.castle_available[1] = false,
                 7 => self.castle_available[0] = false,
                 56 => self.castle_available[3] = false,
                 63 => self.castle_available[2] = false,
                 _ => (),
             }
-        } else if piece == 0 {
-            match piece_move.special {
-                SpecialMove::KnightPromotion => {
-                    self.piece_positions[color as usize][piece as usize] &=
-                        !(1u64 << piece_move.end);
-                    self.piece_positions[color as usize][1] |= 1u64 << piece_move.end;
-                    self.square_to_piece[piece_move.end as usize] = 1;
-                }
-                SpecialMove::BishopPromotion => {
-                    self.piece_positions[color as usize][piece as usize] &=
-                        !(1u64 << piece_move.end);
-                    self.piece_positions[color as usize][2] |= 1u64 << piece_move.end;
-                    self.square_to_piece[piece_move.end as usize] = 2;
-                }
-                SpecialMove::RookPromotion => {
-                    self.piece_positions[color as usize][piece as usize] &=
-                        !(1u64 << piece_move.end);
-                    self.piece_positions[color as usize][3] |= 1u64 << piece_move.end;
-                    self.square_to_piece[piece_move.end as usize] = 3;
-                }
-                SpecialMove::QueenPromotion => {
-                    self.piece_positions[color as usize][piece as usize] &=
-                        !(1u64 << piece_move.end);
-                    self.piece_positions[color as usize][4] |= 1u64 << piece_move.end;
-                    self.square_to_piece[piece_move.end as usize] = 4;
-                }
-                SpecialMove::EnPassant => {
-                    if color == 0 {
-                        if piece_move.end - piece_move.start == 7 {
-                            self.piece_positions[opponent_color as usize][0] &=
-                                !(1u64 << (piece_move.start - 1));
-                            self.square_exists[piece_move.start as usize - 1] = false;
-                            self.square_to_color[piece_move.start as usize - 1] = 0;
-                            self.square_to_piece[piece_move.start as usize - 1] = 0;
-                        } else {
-                            self.piece_positions[opponent_color as usize][0] &=
-                                !(1u64 << (piece_move.start + 1));
-                            self.square_exists[piece_move.start as usize + 1] = false;
-                            self.square_to_color[piece_move.start as usize + 1] = 0;
-                            self.square_to_piece[piece_move.start as usize + 1] = 0;
-                        }
-                    } else {
-                        if piece_move.start - piece_move.end == 7 {
-                            self.piece_positions[opponent_color as usize][0] &=
-                                !(1u64 << (piece_move.start + 1));
-                            self.square_exists[piece_move.start as usize + 1] = false;
-                            self.square_to_color[piece_move.start as usize + 1] = 0;
-                            self.square_to_piece[piece_move.start as usize + 1] = 0;
-                        } else {
-                            self.piece_positions[opponent_color as usize][0] &=
-                                !(1u64 << (piece_move.start - 1));
-                            self.square_exists[piece_move.start as usize - 1] = false;
-                            self.square_to_color[piece_move.start as usize - 1] = 0;
-                            self.square_to_piece[piece_move.start as usize - 1] = 0;
-                        }
-                    }
-                }
-                _ => (),
+        } else if piece == Piece::Pawn {
+            if (start as i8 - end as i8).abs() == 16 {
+                self.en_passant = Some(Square::new(((start + end) / 2) as u8));
+            }
+            if let Some(promotion) = piece_move.special.promotion_piece() {
+                self.piece_positions[color as usize][Piece::Pawn as usize] &= !(1u64 << end);
+                self.piece_positions[color as usize][promotion as usize] |= 1u64 << end;
+                self.square_to_piece[end] = promotion;
+                self.hash ^= zobrist::piece_key(color, Piece::Pawn, piece_move.end)
+                    ^ zobrist::piece_key(color, promotion, piece_move.end);
+            } else if piece_move.special == SpecialMove::EnPassant {
+                let captured = Game::en_passant_capture_square(piece_move);
+                self.piece_positions[opponent_color as usize][Piece::Pawn as usize] &=
+                    !(1u64 << captured);
+                self.hash ^=
+                    zobrist::piece_key(opponent_color, Piece::Pawn, Square::new(captured as u8));
+                self.clear_square(captured);
+            }
+        }
+        if let Some(en_passant) = prev_en_passant {
+            self.hash ^= zobrist::en_passant_key(en_passant);
+        }
+        if let Some(en_passant) = self.en_passant {
+            self.hash ^= zobrist::en_passant_key(en_passant);
+        }
+        for (castle_idx, prev_available) in prev_castle_available.iter().enumerate() {
+            if *prev_available != self.castle_available[castle_idx] {
+                self.hash ^= zobrist::castle_key(castle_idx);
             }
         }
+        self.hash ^= zobrist::side_key();
         let prev_game_state = PrevGameState {
             capture_piece,
             is_capture,
             castle_available: prev_castle_available,
+            en_passant: prev_en_passant,
+            halfmove_clock: prev_halfmove_clock,
+            fullmove_number: prev_fullmove_number,
+            hash: prev_hash,
         };
         self.set_moves();
         prev_game_state
     }
-    // WARNING: Doesn't work with en passant
     pub fn unmake_move(
         &mut self,
-        color: u8,
-        piece: u8,
+        color: Color,
+        piece: Piece,
         piece_move: &PieceMove,
         prev_game_state: &PrevGameState,
     ) {
-        self.square_exists[piece_move.end as usize] = false;
-        self.square_to_color[piece_move.end as usize] = 0;
-        self.square_to_piece[piece_move.end as usize] = 0;
-        let opponent_color = color ^ 1;
+        let start = piece_move.start.index();
+        let end = piece_move.end.index();
+        self.clear_square(end);
+        let opponent_color = !color;
         if prev_game_state.is_capture {
             self.piece_positions[opponent_color as usize]
-                [prev_game_state.capture_piece as usize] |= 1u64 << piece_move.end;
-            self.square_exists[piece_move.end as usize] = true;
-            self.square_to_color[piece_move.end as usize] = opponent_color;
-            self.square_to_piece[piece_move.end as usize] = prev_game_state.capture_piece;
+                [prev_game_state.capture_piece as usize] |= 1u64 << end;
+            self.fill_square(end, opponent_color, prev_game_state.capture_piece);
         }
-        self.square_exists[piece_move.start as usize] = true;
-        self.square_to_color[piece_move.start as usize] = color;
-        self.square_to_piece[piece_move.start as usize] = piece;
-        self.piece_positions[color as usize][piece as usize] |= 1u64 << piece_move.start;
-        self.piece_positions[color as usize][piece as usize] &= !(1u64 << piece_move.end);
+        self.fill_square(start, color, piece);
+        self.piece_positions[color as usize][piece as usize] |= 1u64 << start;
+        self.piece_positions[color as usize][piece as usize] &= !(1u64 << end);
         self.castle_available = prev_game_state.castle_available;
-        if piece == 5 {
-            if piece_move.special == SpecialMove::CastleKingside {
-                self.piece_positions[color as usize][3] |= 1u64 << (piece_move.end + 1);
-                self.piece_positions[color as usize][3] &= !(1u64 << (piece_move.end - 1));
-                self.square_exists[piece_move.end as usize - 1] = false;
-                self.square_to_color[piece_move.end as usize - 1] = 0;
-                self.square_to_piece[piece_move.end as usize - 1] = 0;
-                self.square_exists[piece_move.end as usize + 1] = true;
-                self.square_to_color[piece_move.end as usize + 1] = color;
-                self.square_to_piece[piece_move.end as usize + 1] = 3;
-            } else if piece_move.special == SpecialMove::CastleQueenside {
-                self.piece_positions[color as usize][3] |= 1u64 << (piece_move.end - 2);
-                self.piece_positions[color as usize][3] &= !(1u64 << (piece_move.end + 1));
-                self.square_exists[piece_move.end as usize + 1] = false;
-                self.square_to_color[piece_move.end as usize + 1] = 0;
-                self.square_to_piece[piece_move.end as usize + 1] = 0;
-                self.square_exists[piece_move.end as usize - 2] = true;
-                self.square_to_color[piece_move.end as usize - 2] = color;
-                self.square_to_piece[piece_move.end as usize - 2] = 3;
+        self.en_passant = prev_game_state.en_passant;
+        self.halfmove_clock = prev_game_state.halfmove_clock;
+        self.fullmove_number = prev_game_state.fullmove_number;
+        self.hash = prev_game_state.hash;
+        self.hash_history.pop();
+        self.side_to_move = color;
+        if piece == Piece::King {
+            if let Some((rook_start, rook_end)) = Game::castle_rook_squares(piece_move) {
+                self.piece_positions[color as usize][Piece::Rook as usize] |= 1u64 << rook_start;
+                self.piece_positions[color as usize][Piece::Rook as usize] &= !(1u64 << rook_end);
+                self.clear_square(rook_end);
+                self.fill_square(rook_start, color, Piece::Rook);
             }
-        } else if piece == 0 {
-            match piece_move.special {
-                SpecialMove::KnightPromotion => {
-                    self.piece_positions[color as usize][piece as usize] |=
-                        1u64 << piece_move.start;
-                    self.piece_positions[color as usize][1] &= !(1u64 << piece_move.end);
-                    self.square_to_piece[piece_move.start as usize] = 0;
-                }
-                SpecialMove::BishopPromotion => {
-                    self.piece_positions[color as usize][piece as usize] |=
-                        1u64 << piece_move.start;
-                    self.piece_positions[color as usize][2] &= !(1u64 << piece_move.end);
-                    self.square_to_piece[piece_move.start as usize] = 0;
-                }
-                SpecialMove::RookPromotion => {
-                    self.piece_positions[color as usize][piece as usize] |=
-                        1u64 << piece_move.start;
-                    self.piece_positions[color as usize][3] &= !(1u64 << piece_move.end);
-                    self.square_to_piece[piece_move.start as usize] = 0;
-                }
-                SpecialMove::QueenPromotion => {
-                    self.piece_positions[color as usize][piece as usize] |=
-                        1u64 << piece_move.start;
-                    self.piece_positions[color as usize][4] &= !(1u64 << piece_move.end);
-                    self.square_to_piece[piece_move.start as usize] = 0;
-                }
-                SpecialMove::EnPassant => {
-                    if color == 0 {
-                        if piece_move.end - piece_move.start == 7 {
-                            self.piece_positions[opponent_color as usize][0] |=
-                                1u64 << (piece_move.start - 1);
-                            self.square_exists[piece_move.start as usize - 1] = true;
-                            self.square_to_color[piece_move.start as usize - 1] = opponent_color;
-                            self.square_to_piece[piece_move.start as usize - 1] = 0;
-                        } else {
-                            self.piece_positions[opponent_color as usize][0] |=
-                                1u64 << (piece_move.start + 1);
-                            self.square_exists[piece_move.start as usize + 1] = true;
-                            self.square_to_color[piece_move.start as usize + 1] = opponent_color;
-                            self.square_to_piece[piece_move.start as usize + 1] = 0;
-                        }
-                    } else {
-                        if piece_move.start - piece_move.end == 7 {
-                            self.piece_positions[opponent_color as usize][0] |=
-                                1u64 << (piece_move.start + 1);
-                            self.square_exists[piece_move.start as usize + 1] = true;
-                            self.square_to_color[piece_move.start as usize + 1] = opponent_color;
-                            self.square_to_piece[piece_move.start as usize + 1] = 0;
-                        } else {
-                            self.piece_positions[opponent_color as usize][0] |=
-                                1u64 << (piece_move.start - 1);
-                            self.square_exists[piece_move.start as usize - 1] = true;
-                            self.square_to_color[piece_move.start as usize - 1] = opponent_color;
-                            self.square_to_piece[piece_move.start as usize - 1] = 0;
-                        }
-                    }
-                }
-                _ => (),
+        } else if piece == Piece::Pawn {
+            if let Some(promotion) = piece_move.special.promotion_piece() {
+                self.piece_positions[color as usize][promotion as usize] &= !(1u64 << end);
+            } else if piece_move.special == SpecialMove::EnPassant {
+                let captured = Game::en_passant_capture_square(piece_move);
+                self.piece_positions[opponent_color as usize][Piece::Pawn as usize] |=
+                    1u64 << captured;
+                self.fill_square(captured, opponent_color, Piece::Pawn);
             }
         }
         self.set_moves();
     }
+    // where the rook starts and ends up when castling
+    fn castle_rook_squares(piece_move: &PieceMove) -> Option<(usize, usize)> {
+        let end = piece_move.end.index();
+        match piece_move.special {
+            SpecialMove::CastleKingside => Some((end + 1, end - 1)),
+            SpecialMove::CastleQueenside => Some((end - 2, end + 1)),
+            _ => None,
+        }
+    }
+    // the captured pawn sits beside the capturing pawn, on the file it moves to
+    fn en_passant_capture_square(piece_move: &PieceMove) -> usize {
+        piece_move.start.index() - piece_move.start.file() as usize + piece_move.end.file() as usize
+    }
+    fn clear_square(&mut self, square: usize) {
+        self.square_exists[square] = false;
+        self.square_to_color[square] = Color::White;
+        self.square_to_piece[square] = Piece::Pawn;
+    }
+    fn fill_square(&mut self, square: usize, color: Color, piece: Piece) {
+        self.square_exists[square] = true;
+        self.square_to_color[square] = color;
+        self.square_to_piece[square] = piece;
+    }
     pub fn set_moves(&mut self) {
         let mut blockers = 0;
         for color in 0..self.piece_positions.len() {
@@ -323,59 +351,99 @@ impl Game {
                 self.square_moves[square] = self.move_gen.gen_move(
                     self.square_to_color[square],
                     self.square_to_piece[square],
-                    square as u8,
+                    Square::new(square as u8),
                     blockers,
                     self.castle_available,
+                    self.en_passant,
                 );
+            } else {
+                self.square_moves[square].clear();
             }
         }
     }
+    // make_move keeps hash up to date, this is for setting up positions and checking it
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = 0;
+        for square in 0..64 {
+            if self.square_exists[square] {
+                hash ^= zobrist::piece_key(
+                    self.square_to_color[square],
+                    self.square_to_piece[square],
+                    Square::new(square as u8),
+                );
+            }
+        }
+        for castle_idx in 0..4 {
+            if self.castle_available[castle_idx] {
+                hash ^= zobrist::castle_key(castle_idx);
+            }
+        }
+        if let Some(en_passant) = self.en_passant {
+            hash ^= zobrist::en_passant_key(en_passant);
+        }
+        if self.side_to_move == Color::Black {
+            hash ^= zobrist::side_key();
+        }
+        hash
+    }
     pub fn starting_game(&mut self) {
         self.blank_game();
-        for i in 0..8 {
-            self.create_piece(0, 0, i + 8);
-            self.create_piece(1, 0, i + 48);
+        let back_rank = [
+            Piece::Rook,
+            Piece::Knight,
+            Piece::Bishop,
+            Piece::Queen,
+            Piece::King,
+            Piece::Bishop,
+            Piece::Knight,
+            Piece::Rook,
+        ];
+        for (file, piece) in back_rank.iter().enumerate() {
+            let file = file as u8;
+            self.create_piece(Color::White, *piece, Square::from_coords(file, 0));
+            self.create_piece(Color::White, Piece::Pawn, Square::from_coords(file, 1));
+            self.create_piece(Color::Black, Piece::Pawn, Square::from_coords(file, 6));
+            self.create_piece(Color::Black, *piece, Square::from_coords(file, 7));
         }
-        self.create_piece(0, 1, 1);
-        self.create_piece(0, 1, 6);
-        self.create_piece(0, 2, 2);
-        self.create_piece(0, 2, 5);
-        self.create_piece(0, 3, 0);
-        self.create_piece(0, 3, 7);
-        self.create_piece(0, 4, 3);
-        self.create_piece(0, 5, 4);
-        self.create_piece(1, 1, 57);
-        self.create_piece(1, 1, 62);
-        self.create_piece(1, 2, 58);
-        self.create_piece(1, 2, 61);
-        self.create_piece(1, 3, 56);
-        self.create_piece(1, 3, 63);
-        self.create_piece(1, 4, 59);
-        self.create_piece(1, 5, 60);
         self.castle_available = [true; 4];
+        self.hash = self.compute_hash();
         self.set_moves();
     }
     pub fn blank_game(&mut self) {
         self.piece_positions = [[0; 6]; 2];
-        self.square_to_color = [0; 64];
-        self.square_to_piece = [0; 64];
+        self.square_to_color = [Color::White; 64];
+        self.square_to_piece = [Piece::Pawn; 64];
         self.square_exists = [false; 64];
         self.square_moves = [ArrayVec::new(); 64];
         self.castle_available = [false; 4];
+        self.side_to_move = Color::White;
+        self.en_passant = None;
+        self.halfmove_clock = 0;
+        self.fullmove_number = 1;
+        self.hash = 0;
+        self.hash_history.clear();
         self.move_gen = MoveGen::new();
     }
-    pub fn create_piece(&mut self, color: u8, piece: u8, position: u8) {
-        self.piece_positions[color as usize][piece as usize] |= 1u64 << position;
-        self.square_to_color[position as usize] = color;
-        self.square_to_piece[position as usize] = piece;
-        self.square_exists[position as usize] = true;
+    pub fn create_piece(&mut self, color: Color, piece: Piece, square: Square) {
+        self.piece_positions[color as usize][piece as usize] |= square.bitboard();
+        self.hash ^= zobrist::piece_key(color, piece, square);
+        self.fill_square(square.index(), color, piece);
     }
-    pub fn delete_piece(&mut self, position: u8) {
-        let color = self.square_to_color[position as usize];
-        let piece = self.square_to_piece[position as usize];
-        self.piece_positions[color as usize][piece as usize] &= !(1u64 << position);
-        self.square_to_color[position as usize] = 0;
-        self.square_to_piece[position as usize] = 0;
-        self.square_exists[position as usize] = false;
+    pub fn delete_piece(&mut self, square: Square) {
+        let color = self.square_to_color[square.index()];
+        let piece = self.square_to_piece[square.index()];
+        self.piece_positions[color as usize][piece as usize] &= !square.bitboard();
+        self.hash ^= zobrist::piece_key(color, piece, square);
+        self.clear_square(square.index());
+    }
+    pub fn piece_at(&self, square: Square) -> Option<(Color, Piece)> {
+        if self.square_exists[square.index()] {
+            Some((
+                self.square_to_color[square.index()],
+                self.square_to_piece[square.index()],
+            ))
+        } else {
+            None
+        }
     }
 }
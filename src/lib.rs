@@ -1,237 +1,354 @@
+use crate::bound::Bound;
+use crate::color::Color;
 use crate::game::Game;
+use crate::piece::Piece;
 use crate::piecemove::PieceMove;
-use num_cpus;
-use std::sync::mpsc;
+use crate::score::Score;
+use crate::searchcontrol::SearchControl;
+use crate::searchinfo::SearchInfo;
+use crate::searchresult::SearchResult;
+use crate::searchstate::SearchState;
+use crate::transpositiontable::TranspositionTable;
+use crate::ttentry::TtEntry;
+use std::sync::{mpsc, Arc};
 use std::thread;
-use std::time::Instant;
 
+pub mod bound;
+pub mod color;
+pub mod engineoptions;
+pub mod epd;
+pub mod epderror;
+pub mod epdposition;
+pub mod epdreport;
+pub mod epdresult;
+pub mod fen;
+pub mod fenerror;
 pub mod game;
 pub mod magics;
+pub mod moveerror;
 pub mod movegen;
 pub mod moveutil;
+pub mod optionerror;
+pub mod parseerror;
+pub mod perft;
+pub mod pgn;
+pub mod pgnerror;
+pub mod pgngame;
+pub mod pgnmove;
+pub mod piece;
 pub mod piecemove;
 pub mod prevgamestate;
 pub mod pstables;
+pub mod san;
+pub mod score;
+pub mod search;
+pub mod searchcontrol;
+pub mod searchhandle;
+pub mod searchinfo;
+pub mod searchlimits;
+pub mod searchresult;
+pub mod searchstate;
 pub mod specialmove;
+pub mod square;
+pub mod transpositiontable;
+pub mod ttentry;
+pub mod uciengine;
+pub mod ucierror;
+pub mod ucimove;
+pub mod zobrist;
 
-const PIECE_SCORES: [f64; 6] = [1.0, 3.0, 3.25, 5.0, 9.0, 10000.0];
+pub const PIECE_SCORES: [Score; 6] = [
+    Score(100),
+    Score(300),
+    Score(325),
+    Score(500),
+    Score(900),
+    Score(10000),
+];
+// ms into a search before root moves get reported as they start
+const CURRMOVE_DELAY: i128 = 1000;
 
+// The score is only exact inside alpha and beta, at or past them it is just a bound and the
+// window has to be widened to find out more.
 pub fn best_move(
     game: &mut Game,
-    color: u8,
     depth: u8,
-    search_time: i128,
-) -> Option<(PieceMove, f64)> {
-    let start_time = Instant::now();
-    let mut best_move = (
-        PieceMove::default(),
-        if color == 0 {
-            f64::NEG_INFINITY
-        } else {
-            f64::INFINITY
-        },
-    );
+    search_moves: &[PieceMove],
+    alpha: Score,
+    beta: Score,
+    control: &Arc<SearchControl>,
+    tt: &Arc<TranspositionTable>,
+) -> Option<SearchResult> {
+    let color = game.side_to_move;
+    let mut best_move = SearchResult {
+        best_move: PieceMove::default(),
+        score: -Score::INFINITY,
+        pv: Vec::new(),
+    };
     let mut moves = Vec::new();
-    for square in 0..64 {
-        if !game.square_exists[square] || game.square_to_color[square] != color {
+    let tt_move = tt.probe(game.hash).and_then(|tt_entry| tt_entry.best_move);
+    let root_state = SearchState::default();
+    for piece_move in moveutil::ordered_moves(game, color, tt_move, &root_state, 0) {
+        if control.should_stop() {
+            return None;
+        }
+        if !search_moves.is_empty() && !search_moves.contains(&piece_move) {
             continue;
         }
-        let piece = game.square_to_piece[square];
-        for move_idx in 0..game.square_moves[square].len() {
-            if start_time.elapsed().as_millis() as i128 > search_time {
-                return None;
-            }
-            let piece_move = game.square_moves[square][move_idx as usize];
-            if !moveutil::legal_move(game, color, piece, &piece_move) {
-                continue;
-            }
-            moves.push(piece_move);
+        let piece = game.square_to_piece[piece_move.start.index()];
+        if !moveutil::legal_move(game, color, piece, &piece_move) {
+            continue;
         }
+        moves.push(piece_move);
     }
-    let cores = num_cpus::get();
-    let mut grouped_moves = vec![Vec::new(); cores];
+    let threads = control.options().threads;
+    let mut grouped_moves = vec![Vec::new(); threads];
     for (i, piece_move) in moves.into_iter().enumerate() {
-        let group = i % cores;
-        grouped_moves[group].push(piece_move);
+        let group = i % threads;
+        grouped_moves[group].push((i + 1, piece_move));
     }
     let (tx, rx) = mpsc::channel();
     for move_group in grouped_moves {
+        let mut best_move = best_move.clone();
         let mut game = game.clone();
         let tx = tx.clone();
+        let control = Arc::clone(control);
+        let tt = Arc::clone(tt);
         thread::spawn(move || {
-            for piece_move in move_group {
-                let piece = game.square_to_piece[piece_move.start as usize];
+            let mut state = SearchState::default();
+            for (move_idx, (move_number, piece_move)) in move_group.into_iter().enumerate() {
+                if control.elapsed() > CURRMOVE_DELAY {
+                    control.report(SearchInfo::CurrMove {
+                        depth,
+                        piece_move,
+                        number: move_number,
+                    });
+                }
+                let piece = game.square_to_piece[piece_move.start.index()];
                 let prev_game_state = game.make_move(color, piece, &piece_move);
-                let new_time = search_time - start_time.elapsed().as_millis() as i128 as i128;
-                let score = {
-                    if color == 0 {
-                        min(&mut game, best_move.1, f64::INFINITY, depth - 1, new_time)
-                    } else {
-                        max(
+                let child_ply = control.ply(game.hash_history.len());
+                let alpha = alpha.max(best_move.score);
+                let score = if move_idx == 0 {
+                    negamax(
+                        &mut game,
+                        -beta,
+                        -alpha,
+                        depth - 1,
+                        &mut state,
+                        &control,
+                        &tt,
+                    )
+                } else {
+                    let zero_window = -alpha - Score(1);
+                    match negamax(
+                        &mut game,
+                        zero_window,
+                        -alpha,
+                        depth - 1,
+                        &mut state,
+                        &control,
+                        &tt,
+                    ) {
+                        Some(score) if -score > alpha && -score < beta => negamax(
                             &mut game,
-                            f64::NEG_INFINITY,
-                            best_move.1,
+                            -beta,
+                            -alpha,
                             depth - 1,
-                            new_time,
-                        )
+                            &mut state,
+                            &control,
+                            &tt,
+                        ),
+                        score => score,
                     }
                 };
                 game.unmake_move(color, piece, &piece_move, &prev_game_state);
                 match score {
                     Some(score) => {
-                        if color == 0 {
-                            if score > best_move.1 {
-                                best_move = (piece_move, score);
-                            }
-                        } else {
-                            if score < best_move.1 {
-                                best_move = (piece_move, score);
-                            }
+                        if -score > best_move.score {
+                            let mut pv = vec![piece_move];
+                            pv.extend_from_slice(state.pv(child_ply));
+                            best_move = SearchResult {
+                                best_move: piece_move,
+                                score: -score,
+                                pv,
+                            };
                         }
+                        if best_move.score >= beta {
+                            break;
+                        }
+                    }
+                    None => {
+                        tx.send(None).expect("Failed to send result of search");
+                        return;
                     }
-                    None => tx.send(None).expect("Failed to send result of search"),
                 }
             }
             tx.send(Some(best_move))
                 .expect("Failed to send result of search");
         });
     }
-    for _ in 0..cores {
+    for _ in 0..threads {
         let potential_best_move = rx.recv().expect("Failed to read from thread receiver.");
         match potential_best_move {
             Some(potential_best_move) => {
-                if color == 0 {
-                    if potential_best_move.1 > best_move.1 {
-                        best_move = potential_best_move;
-                    }
-                } else {
-                    if potential_best_move.1 < best_move.1 {
-                        best_move = potential_best_move;
-                    }
+                if potential_best_move.score > best_move.score {
+                    best_move = potential_best_move;
                 }
             }
             None => return None,
         }
     }
+    if best_move.score.abs() < Score::INFINITY {
+        tt.store(
+            game.hash,
+            TtEntry {
+                depth,
+                bound: if best_move.score >= beta {
+                    Bound::Lower
+                } else if best_move.score <= alpha {
+                    Bound::Upper
+                } else {
+                    Bound::Exact
+                },
+                score: best_move.score,
+                best_move: Some(best_move.best_move),
+            },
+        );
+    }
     Some(best_move)
 }
 
-pub fn max(
+// leaves the line that raised alpha in state's pv for this ply, cut short where the table or
+// quiescence took over
+pub fn negamax(
     game: &mut Game,
-    mut alpha: f64,
-    beta: f64,
+    mut alpha: Score,
+    beta: Score,
     depth: u8,
-    search_time: i128,
-) -> Option<f64> {
-    let start_time = Instant::now();
-    if game.game_over(0) {
-        return Some(eval(game, 0));
-    }
-    if depth <= 0 {
-        return q_max(
-            game,
-            alpha,
-            beta,
-            search_time - start_time.elapsed().as_millis() as i128,
-        );
+    state: &mut SearchState,
+    control: &SearchControl,
+    tt: &TranspositionTable,
+) -> Option<Score> {
+    control.count_node(game.hash_history.len());
+    let ply = control.ply(game.hash_history.len());
+    state.clear_pv(ply);
+    // a repeat inside the search can be repeated again, so treat it as a draw already
+    if game.repetition_count() > 0 {
+        return Some(Score::DRAW);
     }
-    for square in 0..64 {
-        if !game.square_exists[square] || game.square_to_color[square] != 0 {
+    if game.game_over() {
+        return Some(eval(game, ply, &control.options().piece_scores));
+    }
+    if depth == 0 {
+        return quiescence(game, alpha, beta, state, control, tt);
+    }
+    let tt_entry = tt.probe(game.hash);
+    if let Some(tt_entry) = tt_entry {
+        if tt_entry.depth >= depth {
+            let tt_score = Score::from_tt(tt_entry.score, ply);
+            // an exact score inside the window would raise alpha without a pv, so search it again
+            match tt_entry.bound {
+                Bound::Exact | Bound::Lower if tt_score >= beta => return Some(beta),
+                Bound::Exact | Bound::Upper if tt_score <= alpha => return Some(alpha),
+                _ => (),
+            }
+        }
+    }
+    let color = game.side_to_move;
+    let original_alpha = alpha;
+    let mut best_move = None;
+    let mut first_move = true;
+    let tt_move = tt_entry.and_then(|tt_entry| tt_entry.best_move);
+    for piece_move in moveutil::ordered_moves(game, color, tt_move, state, ply) {
+        if control.should_stop() {
+            return None;
+        }
+        let piece = game.square_to_piece[piece_move.start.index()];
+        if !moveutil::legal_move(game, color, piece, &piece_move) {
             continue;
         }
-        let piece = game.square_to_piece[square];
-        for move_idx in 0..game.square_moves[square].len() {
-            if start_time.elapsed().as_millis() as i128 > search_time {
-                return None;
-            }
-            let piece_move = game.square_moves[square][move_idx as usize];
-            if !moveutil::legal_move(game, 0, piece, &piece_move) {
-                continue;
-            }
-            let prev_game_state = game.make_move(0, piece, &piece_move);
-            let score = min(
+        let prev_game_state = game.make_move(color, piece, &piece_move);
+        // with good ordering the first move is the best, the rest only have to be shown to be
+        // worse, which a zero window does cheaply, and the few that aren't get searched again
+        let score = if first_move {
+            negamax(game, -beta, -alpha, depth - 1, state, control, tt)
+        } else {
+            match negamax(
                 game,
-                alpha,
-                beta,
+                -alpha - Score(1),
+                -alpha,
                 depth - 1,
-                search_time - start_time.elapsed().as_millis() as i128,
-            );
-            game.unmake_move(0, piece, &piece_move, &prev_game_state);
-            match score {
-                Some(score) => {
-                    if score >= beta {
-                        return Some(beta);
-                    }
-                    if score > alpha {
-                        alpha = score;
-                    }
+                state,
+                control,
+                tt,
+            ) {
+                Some(score) if -score > alpha && -score < beta => {
+                    negamax(game, -beta, -alpha, depth - 1, state, control, tt)
                 }
-                None => return None,
+                score => score,
+            }
+        };
+        first_move = false;
+        game.unmake_move(color, piece, &piece_move, &prev_game_state);
+        let score = -score?;
+        if score >= beta {
+            if moveutil::is_quiet(game, color, &piece_move) {
+                state.record_cutoff(color, ply, piece_move, depth);
             }
+            tt.store(
+                game.hash,
+                TtEntry {
+                    depth,
+                    bound: Bound::Lower,
+                    score: beta.to_tt(ply),
+                    best_move: Some(piece_move),
+                },
+            );
+            return Some(beta);
+        }
+        if score > alpha {
+            alpha = score;
+            best_move = Some(piece_move);
+            state.update_pv(ply, piece_move);
         }
     }
+    tt.store(
+        game.hash,
+        TtEntry {
+            depth,
+            bound: if alpha > original_alpha {
+                Bound::Exact
+            } else {
+                Bound::Upper
+            },
+            score: alpha.to_tt(ply),
+            best_move,
+        },
+    );
     Some(alpha)
 }
-pub fn min(
+pub fn quiescence(
     game: &mut Game,
-    alpha: f64,
-    mut beta: f64,
-    depth: u8,
-    search_time: i128,
-) -> Option<f64> {
-    let start_time = Instant::now();
-    if game.game_over(1) {
-        return Some(eval(game, 1));
-    }
-    if depth <= 0 {
-        return q_min(
-            game,
-            alpha,
-            beta,
-            search_time - start_time.elapsed().as_millis() as i128,
-        );
-    }
-    for square in 0..64 {
-        if !game.square_exists[square] || game.square_to_color[square] != 1 {
-            continue;
-        }
-        let piece = game.square_to_piece[square];
-        for move_idx in 0..game.square_moves[square].len() {
-            if start_time.elapsed().as_millis() as i128 > search_time {
-                return None;
-            }
-            let piece_move = game.square_moves[square][move_idx as usize];
-            if !moveutil::legal_move(game, 1, piece, &piece_move) {
-                continue;
-            }
-            let prev_game_state = game.make_move(1, piece, &piece_move);
-            let score = max(
-                game,
-                alpha,
-                beta,
-                depth - 1,
-                search_time - start_time.elapsed().as_millis() as i128,
-            );
-            game.unmake_move(1, piece, &piece_move, &prev_game_state);
-            match score {
-                Some(score) => {
-                    if score <= alpha {
-                        return Some(alpha);
-                    }
-                    if score < beta {
-                        beta = score;
-                    }
-                }
-                None => return None,
-            }
+    mut alpha: Score,
+    beta: Score,
+    state: &SearchState,
+    control: &SearchControl,
+    tt: &TranspositionTable,
+) -> Option<Score> {
+    control.count_node(game.hash_history.len());
+    let ply = control.ply(game.hash_history.len());
+    let tt_entry = tt.probe(game.hash);
+    if let Some(tt_entry) = tt_entry {
+        let tt_score = Score::from_tt(tt_entry.score, ply);
+        match tt_entry.bound {
+            Bound::Exact => return Some(tt_score),
+            Bound::Lower if tt_score >= beta => return Some(beta),
+            Bound::Upper if tt_score <= alpha => return Some(alpha),
+            _ => (),
         }
     }
-    Some(beta)
-}
-pub fn q_max(game: &mut Game, mut alpha: f64, beta: f64, search_time: i128) -> Option<f64> {
-    let start_time = Instant::now();
-    let stand_pat = eval(game, 0);
-    if game.game_over(0) {
+    let stand_pat = eval(game, ply, &control.options().piece_scores);
+    if game.game_over() {
         return Some(stand_pat);
     }
     if stand_pat >= beta {
@@ -240,108 +357,68 @@ pub fn q_max(game: &mut Game, mut alpha: f64, beta: f64, search_time: i128) -> O
     if stand_pat > alpha {
         alpha = stand_pat;
     }
-    for square in 0..64 {
-        if !game.square_exists[square] || game.square_to_color[square] != 0 {
+    let color = game.side_to_move;
+    let original_alpha = alpha;
+    let mut best_move = None;
+    let tt_move = tt_entry.and_then(|tt_entry| tt_entry.best_move);
+    for piece_move in moveutil::ordered_moves(game, color, tt_move, state, ply) {
+        if control.should_stop() {
+            return None;
+        }
+        if !game.square_exists[piece_move.end.index()] {
             continue;
         }
-        let piece = game.square_to_piece[square];
-        for move_idx in 0..game.square_moves[square].len() {
-            if start_time.elapsed().as_millis() as i128 > search_time {
-                return None;
-            }
-            let piece_move = game.square_moves[square][move_idx as usize];
-            if !game.square_exists[piece_move.end as usize] {
-                continue;
-            }
-            if !moveutil::legal_move(game, 0, piece, &piece_move) {
-                continue;
-            }
-            if see(game, &piece_move) < 0.0 {
-                continue;
-            }
-            let prev_game_state = game.make_move(0, piece, &piece_move);
-            let score = q_min(
-                game,
-                alpha,
-                beta,
-                search_time - start_time.elapsed().as_millis() as i128,
-            );
-            game.unmake_move(0, piece, &piece_move, &prev_game_state);
-            match score {
-                Some(score) => {
-                    if score >= beta {
-                        return Some(beta);
-                    }
-                    if score > alpha {
-                        alpha = score;
-                    }
-                }
-                None => return None,
-            }
+        let piece = game.square_to_piece[piece_move.start.index()];
+        if !moveutil::legal_move(game, color, piece, &piece_move) {
+            continue;
         }
-    }
-    Some(alpha)
-}
-pub fn q_min(game: &mut Game, alpha: f64, mut beta: f64, search_time: i128) -> Option<f64> {
-    let start_time = Instant::now();
-    let stand_pat = eval(game, 1);
-    if game.game_over(1) {
-        return Some(stand_pat);
-    }
-    if stand_pat <= alpha {
-        return Some(alpha);
-    }
-    if stand_pat < beta {
-        beta = stand_pat;
-    }
-    for square in 0..64 {
-        if !game.square_exists[square] || game.square_to_color[square] != 1 {
+        if control.options().q_see_pruning
+            && see(game, &piece_move, &control.options().piece_scores) < Score::DRAW
+        {
             continue;
         }
-        let piece = game.square_to_piece[square];
-        for move_idx in 0..game.square_moves[square].len() {
-            if start_time.elapsed().as_millis() as i128 > search_time {
-                return None;
-            }
-            let piece_move = game.square_moves[square][move_idx as usize];
-            if !game.square_exists[piece_move.end as usize] {
-                continue;
-            }
-            if !moveutil::legal_move(game, 1, piece, &piece_move) {
-                continue;
-            }
-            if see(game, &piece_move) > 0.0 {
-                continue;
-            }
-            let prev_game_state = game.make_move(1, piece, &piece_move);
-            let score = q_max(
-                game,
-                alpha,
-                beta,
-                search_time - start_time.elapsed().as_millis() as i128,
+        let prev_game_state = game.make_move(color, piece, &piece_move);
+        let score = quiescence(game, -beta, -alpha, state, control, tt);
+        game.unmake_move(color, piece, &piece_move, &prev_game_state);
+        let score = -score?;
+        if score >= beta {
+            tt.store(
+                game.hash,
+                TtEntry {
+                    depth: 0,
+                    bound: Bound::Lower,
+                    score: beta.to_tt(ply),
+                    best_move: Some(piece_move),
+                },
             );
-            game.unmake_move(1, piece, &piece_move, &prev_game_state);
-            match score {
-                Some(score) => {
-                    if score <= alpha {
-                        return Some(alpha);
-                    }
-                    if score < beta {
-                        beta = score;
-                    }
-                }
-                None => return None,
-            }
+            return Some(beta);
+        }
+        if score > alpha {
+            alpha = score;
+            best_move = Some(piece_move);
         }
     }
-    Some(beta)
+    tt.store(
+        game.hash,
+        TtEntry {
+            depth: 0,
+            bound: if alpha > original_alpha {
+                Bound::Exact
+            } else {
+                Bound::Upper
+            },
+            score: alpha.to_tt(ply),
+            best_move,
+        },
+    );
+    Some(alpha)
 }
-pub fn see(game: &mut Game, piece_move: &PieceMove) -> f64 {
-    let color = game.square_to_color[piece_move.start as usize];
-    let piece = game.square_to_piece[piece_move.start as usize];
-    let mut score = PIECE_SCORES[game.square_to_piece[piece_move.end as usize] as usize]
-        * (-(color as f64) * 2.0 + 1.0);
-    let prev_game_state = game.make_move(color, piece, &piece_move);
+// what the side making piece_move wins once the exchange on its end square plays out
+pub fn see(game: &mut Game, piece_move: &PieceMove, piece_scores: &[Score; 6]) -> Score {
+    let color = game.square_to_color[piece_move.start.index()];
+    let piece = game.square_to_piece[piece_move.start.index()];
+    let mut score = piece_scores[game.square_to_piece[piece_move.end.index()] as usize];
+    let prev_game_state = game.make_move(color, piece, piece_move);
     let mut lowest_attacker_square = -1;
     let mut lowest_attacker_move = PieceMove::default();
     for square in 0..64 {
@@ -351,8 +428,8 @@ pub fn see(game: &mut Game, piece_move: &PieceMove) -> f64 {
         let mut capture_idx = 0;
         let mut found = false;
         for move_idx in 0..game.square_moves[square].len() {
-            if game.square_moves[square][move_idx as usize].start == square as u8
-                && game.square_moves[square][move_idx as usize].end == piece_move.end
+            if game.square_moves[square][move_idx].start.index() == square
+                && game.square_moves[square][move_idx].end == piece_move.end
             {
                 capture_idx = move_idx;
                 found = true;
@@ -362,7 +439,7 @@ pub fn see(game: &mut Game, piece_move: &PieceMove) -> f64 {
         if !found {
             continue;
         }
-        let capture_move = game.square_moves[square][capture_idx as usize];
+        let capture_move = game.square_moves[square][capture_idx];
         let square_color = game.square_to_color[square];
         let square_piece = game.square_to_piece[square];
         if !moveutil::legal_move(game, square_color, square_piece, &capture_move) {
@@ -373,147 +450,574 @@ pub fn see(game: &mut Game, piece_move: &PieceMove) -> f64 {
         {
             lowest_attacker_square = square as i32;
             lowest_attacker_move = capture_move;
-            if square_piece == 0 {
+            if square_piece == Piece::Pawn {
                 break;
             }
         }
     }
     if lowest_attacker_square != -1 {
-        score += see(game, &lowest_attacker_move);
+        score -= see(game, &lowest_attacker_move, piece_scores);
     }
     game.unmake_move(color, piece, piece_move, &prev_game_state);
-    return score;
+    score
 }
-pub fn eval(game: &mut Game, color: u8) -> f64 {
-    if game.in_stalemate(color) {
-        return 0 as f64;
+// From the side to move's point of view. ply is how far game is from the root, so nearer
+// checkmates score higher.
+pub fn eval(game: &mut Game, ply: usize, piece_scores: &[Score; 6]) -> Score {
+    if game.in_stalemate(game.side_to_move) {
+        return Score::DRAW;
     }
-    if game.in_checkmate(0) {
-        return -10000 as f64;
+    if game.in_checkmate(game.side_to_move) {
+        return -Score::mate_in(ply);
     }
-    if game.in_checkmate(1) {
-        return 10000 as f64;
+    if game.fifty_move_draw() || game.threefold_repetition() {
+        return Score::DRAW;
     }
-    let mut score = 0.0;
-    for piece in 0..(game.piece_positions[0].len() - 1) {
-        let wscore = game.piece_positions[0][piece].count_ones() as f64 * PIECE_SCORES[piece];
-        let bscore = game.piece_positions[1][piece].count_ones() as f64 * PIECE_SCORES[piece];
+    let mut score = 0;
+    for (piece, piece_score) in piece_scores.iter().enumerate().take(5) {
+        let wscore = game.piece_positions[0][piece].count_ones() as i32 * piece_score.0;
+        let bscore = game.piece_positions[1][piece].count_ones() as i32 * piece_score.0;
         score += wscore;
         score -= bscore;
         for square in 0..64 {
             // TODO implement unsymmetrical tables
             if game.square_exists[square] {
-                if game.square_to_color[square] == 0 {
-                    score += pstables::PIECE_SQUARE_TABLES[piece][square] as f64 / 100.0;
+                if game.square_to_color[square] == Color::White {
+                    score += pstables::PIECE_SQUARE_TABLES[piece][square];
                 } else {
-                    score -= pstables::PIECE_SQUARE_TABLES[piece][63 - square] as f64 / 100.0;
+                    score -= pstables::PIECE_SQUARE_TABLES[piece][63 - square];
                 }
             }
         }
     }
-    return score;
+    if game.side_to_move == Color::Black {
+        score = -score;
+    }
+    Score(score)
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::bound::Bound;
+    use crate::color::Color;
     use crate::game::Game;
+    use crate::piece::Piece;
     use crate::piecemove::PieceMove;
+    use crate::score::Score;
+    use crate::searchcontrol::SearchControl;
+    use crate::searchresult::SearchResult;
+    use crate::searchstate::SearchState;
     use crate::specialmove::SpecialMove;
+    use crate::square::Square;
+    use crate::transpositiontable::TranspositionTable;
+    use crate::ttentry::TtEntry;
+    use std::sync::Arc;
 
     #[test]
     fn test_checkmates() {
         //rook back-rank
         let mut game = Game::new();
-        game.create_piece(1, 5, 0);
-        game.create_piece(0, 5, 16);
-        game.create_piece(0, 3, 15);
+        game.create_piece(Color::Black, Piece::King, Square::A1);
+        game.create_piece(Color::White, Piece::King, Square::A3);
+        game.create_piece(Color::White, Piece::Rook, Square::H2);
         game.set_moves();
         assert_eq!(
-            crate::best_move(&mut game, 0, 1, i128::max_value()).unwrap(),
-            (
-                PieceMove {
-                    start: 15,
-                    end: 7,
+            crate::best_move(
+                &mut game,
+                1,
+                &[],
+                -Score::INFINITY,
+                Score::INFINITY,
+                &Arc::new(SearchControl::unlimited()),
+                &Arc::new(TranspositionTable::new(1))
+            )
+            .unwrap(),
+            SearchResult {
+                best_move: PieceMove {
+                    start: Square::H2,
+                    end: Square::H1,
                     special: SpecialMove::None,
                 },
-                10000.0
+                score: Score::mate_in(1),
+                pv: vec![PieceMove {
+                    start: Square::H2,
+                    end: Square::H1,
+                    special: SpecialMove::None,
+                }],
+            }
+        );
+        // scores are the mover's, so the mirrored mate for black scores the same
+        let mut game = Game::from_fen("8/8/8/8/8/k7/6r1/K7 b - - 0 1").unwrap();
+        let mate = game.parse_uci_move("g2g1").unwrap();
+        assert_eq!(
+            crate::best_move(
+                &mut game,
+                3,
+                &[],
+                -Score::INFINITY,
+                Score::INFINITY,
+                &Arc::new(SearchControl::unlimited()),
+                &Arc::new(TranspositionTable::new(1))
             )
+            .unwrap(),
+            SearchResult {
+                best_move: mate,
+                score: Score::mate_in(1),
+                pv: vec![mate],
+            }
         );
     }
     #[test]
     fn test_stalemates() {
         let mut game = Game::new();
-        game.create_piece(1, 5, 0);
-        game.create_piece(1, 2, 10);
-        game.create_piece(0, 4, 11);
-        game.create_piece(0, 5, 63);
+        game.create_piece(Color::Black, Piece::King, Square::A1);
+        game.create_piece(Color::Black, Piece::Bishop, Square::C2);
+        game.create_piece(Color::White, Piece::Queen, Square::D2);
+        game.create_piece(Color::White, Piece::King, Square::H8);
         game.set_moves();
-        let best_move = crate::best_move(&mut game, 0, 1, i128::max_value()).unwrap();
-        assert_ne!((best_move.0.start, best_move.0.end), (11, 10));
+        let best_move = crate::best_move(
+            &mut game,
+            1,
+            &[],
+            -Score::INFINITY,
+            Score::INFINITY,
+            &Arc::new(SearchControl::unlimited()),
+            &Arc::new(TranspositionTable::new(1)),
+        )
+        .unwrap();
+        assert_ne!(
+            (best_move.best_move.start, best_move.best_move.end),
+            (Square::D2, Square::C2)
+        );
     }
     #[test]
     fn test_material_gain() {
         let mut game = Game::new();
-        game.create_piece(1, 5, 0);
-        game.create_piece(1, 4, 16);
-        game.create_piece(0, 5, 63);
-        game.create_piece(0, 1, 4);
+        game.create_piece(Color::Black, Piece::King, Square::A1);
+        game.create_piece(Color::Black, Piece::Queen, Square::A3);
+        game.create_piece(Color::White, Piece::King, Square::H8);
+        game.create_piece(Color::White, Piece::Knight, Square::E1);
         game.set_moves();
-        let best_move = crate::best_move(&mut game, 0, 3, i128::max_value()).unwrap();
-        assert_eq!((best_move.0.start, best_move.0.end), (4, 10));
+        let best_move = crate::best_move(
+            &mut game,
+            3,
+            &[],
+            -Score::INFINITY,
+            Score::INFINITY,
+            &Arc::new(SearchControl::unlimited()),
+            &Arc::new(TranspositionTable::new(1)),
+        )
+        .unwrap();
+        assert_eq!(
+            (best_move.best_move.start, best_move.best_move.end),
+            (Square::E1, Square::C2)
+        );
     }
     #[test]
     fn test_check_functions() {
         let mut game = Game::new();
-        game.create_piece(1, 5, 0);
-        game.create_piece(0, 5, 16);
-        game.create_piece(0, 3, 7);
+        game.create_piece(Color::Black, Piece::King, Square::A1);
+        game.create_piece(Color::White, Piece::King, Square::A3);
+        game.create_piece(Color::White, Piece::Rook, Square::H1);
         game.set_moves();
-        assert!(game.in_check(1));
-        assert!(game.in_checkmate(1));
+        assert!(game.in_check(Color::Black));
+        assert!(game.in_checkmate(Color::Black));
     }
     #[test]
     fn test_movegen() {
         //TODO Add more tests
         let mut game = Game::new();
-        game.create_piece(0, 0, 8);
+        game.create_piece(Color::White, Piece::Pawn, Square::A2);
         game.set_moves();
         assert_eq!(
             game.square_moves[8][0..2],
             [
                 PieceMove {
-                    start: 8,
-                    end: 16,
+                    start: Square::A2,
+                    end: Square::A3,
                     special: SpecialMove::None,
                 },
                 PieceMove {
-                    start: 8,
-                    end: 24,
+                    start: Square::A2,
+                    end: Square::A4,
                     special: SpecialMove::None,
                 }
             ][..]
         )
     }
     #[test]
+    fn test_en_passant() {
+        let mut game = Game::new();
+        game.create_piece(Color::White, Piece::King, Square::E1);
+        game.create_piece(Color::Black, Piece::King, Square::E8);
+        game.create_piece(Color::White, Piece::Pawn, Square::E2);
+        game.create_piece(Color::Black, Piece::Pawn, Square::F4);
+        game.set_moves();
+        let double_push = PieceMove {
+            start: Square::E2,
+            end: Square::E4,
+            special: SpecialMove::None,
+        };
+        let before = game.clone();
+        let prev_game_state = game.make_move(Color::White, Piece::Pawn, &double_push);
+        assert_eq!(game.en_passant, Some(Square::E3));
+        let en_passant = PieceMove {
+            start: Square::F4,
+            end: Square::E3,
+            special: SpecialMove::EnPassant,
+        };
+        assert!(game.square_moves[29].contains(&en_passant));
+        assert!(crate::moveutil::legal_move(
+            &mut game,
+            Color::Black,
+            Piece::Pawn,
+            &en_passant
+        ));
+        let after_push = game.clone();
+        let prev_en_passant_state = game.make_move(Color::Black, Piece::Pawn, &en_passant);
+        assert!(!game.square_exists[28]);
+        assert_eq!(game.piece_positions[0][0], 0);
+        assert_eq!(game.en_passant, None);
+        game.unmake_move(
+            Color::Black,
+            Piece::Pawn,
+            &en_passant,
+            &prev_en_passant_state,
+        );
+        assert!(game == after_push);
+        game.unmake_move(Color::White, Piece::Pawn, &double_push, &prev_game_state);
+        assert!(game == before);
+    }
+    #[test]
+    fn test_fifty_move_rule() {
+        let mut game = Game::new();
+        game.create_piece(Color::White, Piece::King, Square::A1);
+        game.create_piece(Color::Black, Piece::King, Square::H8);
+        game.create_piece(Color::White, Piece::Rook, Square::B2);
+        game.create_piece(Color::White, Piece::Pawn, Square::E2);
+        game.set_moves();
+        let rook_move = PieceMove {
+            start: Square::B2,
+            end: Square::B3,
+            special: SpecialMove::None,
+        };
+        let prev_game_state = game.make_move(Color::White, Piece::Rook, &rook_move);
+        assert_eq!((game.halfmove_clock, game.fullmove_number), (1, 1));
+        game.unmake_move(Color::White, Piece::Rook, &rook_move, &prev_game_state);
+        assert_eq!((game.halfmove_clock, game.fullmove_number), (0, 1));
+        game.halfmove_clock = 99;
+        let prev_game_state = game.make_move(Color::White, Piece::Rook, &rook_move);
+        assert!(game.fifty_move_draw());
+        assert!(game.game_over());
+        assert_eq!(crate::eval(&mut game, 0, &crate::PIECE_SCORES), Score::DRAW);
+        game.unmake_move(Color::White, Piece::Rook, &rook_move, &prev_game_state);
+        let pawn_move = PieceMove {
+            start: Square::E2,
+            end: Square::E3,
+            special: SpecialMove::None,
+        };
+        game.make_move(Color::White, Piece::Pawn, &pawn_move);
+        assert_eq!(game.halfmove_clock, 0);
+        assert!(!game.game_over());
+    }
+    #[test]
+    fn test_side_to_move() {
+        let mut game = Game::new();
+        game.starting_game();
+        assert_eq!(game.side_to_move, Color::White);
+        assert_eq!(game.legal_moves().len(), 20);
+        let before = game.clone();
+        let double_push = PieceMove {
+            start: Square::E2,
+            end: Square::E4,
+            special: SpecialMove::None,
+        };
+        let prev_game_state = game.play_move(&double_push);
+        assert_eq!(game.side_to_move, Color::Black);
+        assert_eq!(game.piece_at(Square::E4), Some((Color::White, Piece::Pawn)));
+        assert_eq!(game.legal_moves().len(), 20);
+        assert!(game
+            .legal_moves()
+            .iter()
+            .all(|piece_move| piece_move.start.rank() >= 6));
+        game.undo_move(&double_push, &prev_game_state);
+        assert!(game == before);
+        // undoing a promotion puts the pawn back
+        let mut game = Game::from_fen("8/4P3/8/8/8/8/8/k1K5 w - - 0 1").unwrap();
+        let before = game.clone();
+        let promotion = PieceMove {
+            start: Square::E7,
+            end: Square::E8,
+            special: SpecialMove::KnightPromotion,
+        };
+        let prev_game_state = game.play_move(&promotion);
+        assert_eq!(
+            game.piece_at(Square::E8),
+            Some((Color::White, Piece::Knight))
+        );
+        game.undo_move(&promotion, &prev_game_state);
+        assert!(game == before);
+    }
+    #[test]
+    fn test_transposed_hash() {
+        let knight_move = |start, end| PieceMove {
+            start,
+            end,
+            special: SpecialMove::None,
+        };
+        let mut game = Game::new();
+        game.starting_game();
+        let start_hash = game.hash;
+        game.make_move(
+            Color::White,
+            Piece::Knight,
+            &knight_move(Square::G1, Square::F3),
+        );
+        game.make_move(
+            Color::Black,
+            Piece::Knight,
+            &knight_move(Square::B8, Square::C6),
+        );
+        game.make_move(
+            Color::White,
+            Piece::Knight,
+            &knight_move(Square::B1, Square::C3),
+        );
+        let mut other = Game::new();
+        other.starting_game();
+        other.make_move(
+            Color::White,
+            Piece::Knight,
+            &knight_move(Square::B1, Square::C3),
+        );
+        other.make_move(
+            Color::Black,
+            Piece::Knight,
+            &knight_move(Square::B8, Square::C6),
+        );
+        other.make_move(
+            Color::White,
+            Piece::Knight,
+            &knight_move(Square::G1, Square::F3),
+        );
+        assert_eq!(game.hash, other.hash);
+        assert_eq!(game.hash, game.compute_hash());
+        game.make_move(
+            Color::Black,
+            Piece::Knight,
+            &knight_move(Square::C6, Square::B8),
+        );
+        game.make_move(
+            Color::White,
+            Piece::Knight,
+            &knight_move(Square::C3, Square::B1),
+        );
+        game.make_move(
+            Color::Black,
+            Piece::Knight,
+            &knight_move(Square::G8, Square::F6),
+        );
+        game.make_move(
+            Color::White,
+            Piece::Knight,
+            &knight_move(Square::F3, Square::G1),
+        );
+        game.make_move(
+            Color::Black,
+            Piece::Knight,
+            &knight_move(Square::F6, Square::G8),
+        );
+        assert_eq!(game.hash, start_hash);
+    }
+    #[test]
+    fn test_repetition() {
+        let king_move = |start, end| PieceMove {
+            start,
+            end,
+            special: SpecialMove::None,
+        };
+        let mut game = Game::new();
+        game.create_piece(Color::White, Piece::King, Square::A1);
+        game.create_piece(Color::Black, Piece::King, Square::H8);
+        game.create_piece(Color::White, Piece::Queen, Square::D4);
+        game.set_moves();
+        game.hash = game.compute_hash();
+        for _ in 0..2 {
+            assert!(!game.threefold_repetition());
+            game.make_move(
+                Color::White,
+                Piece::King,
+                &king_move(Square::A1, Square::B1),
+            );
+            game.make_move(
+                Color::Black,
+                Piece::King,
+                &king_move(Square::H8, Square::G8),
+            );
+            game.make_move(
+                Color::White,
+                Piece::King,
+                &king_move(Square::B1, Square::A1),
+            );
+            game.make_move(
+                Color::Black,
+                Piece::King,
+                &king_move(Square::G8, Square::H8),
+            );
+        }
+        assert_eq!(game.repetition_count(), 2);
+        assert!(game.threefold_repetition());
+        assert!(game.game_over());
+        assert_eq!(crate::eval(&mut game, 0, &crate::PIECE_SCORES), Score::DRAW);
+        // a queen up, but the position already came up
+        assert_eq!(
+            crate::negamax(
+                &mut game,
+                Score(-100),
+                Score(100),
+                2,
+                &mut SearchState::default(),
+                &SearchControl::unlimited(),
+                &TranspositionTable::new(1)
+            ),
+            Some(Score::DRAW)
+        );
+        let prev_game_state = game.make_move(
+            Color::White,
+            Piece::Queen,
+            &king_move(Square::D4, Square::D5),
+        );
+        assert_eq!(game.repetition_count(), 0);
+        game.unmake_move(
+            Color::White,
+            Piece::Queen,
+            &king_move(Square::D4, Square::D5),
+            &prev_game_state,
+        );
+        assert_eq!(game.repetition_count(), 2);
+    }
+    #[test]
+    fn test_transposition_table() {
+        let tt = TranspositionTable::new(1);
+        let entry = TtEntry {
+            depth: 5,
+            bound: Bound::Lower,
+            score: Score(-325),
+            best_move: Some(PieceMove {
+                start: Square::E7,
+                end: Square::E8,
+                special: SpecialMove::QueenPromotion,
+            }),
+        };
+        assert_eq!(tt.probe(12345), None);
+        tt.store(12345, entry);
+        assert_eq!(tt.probe(12345), Some(entry));
+        // same slot, different position
+        assert_eq!(tt.probe(12345 + (1 << 40)), None);
+        // shallower results don't overwrite deeper ones for the same position
+        tt.store(
+            12345,
+            TtEntry {
+                depth: 2,
+                bound: Bound::Exact,
+                score: Score::DRAW,
+                best_move: None,
+            },
+        );
+        assert_eq!(tt.probe(12345), Some(entry));
+        tt.clear();
+        assert_eq!(tt.probe(12345), None);
+        assert!(TranspositionTable::try_new(usize::MAX).is_none());
+    }
+    #[test]
+    fn test_transposition_table_search() {
+        let mut game = Game::new();
+        game.create_piece(Color::Black, Piece::King, Square::A1);
+        game.create_piece(Color::Black, Piece::Queen, Square::A3);
+        game.create_piece(Color::White, Piece::King, Square::H8);
+        game.create_piece(Color::White, Piece::Knight, Square::E1);
+        game.set_moves();
+        let control = Arc::new(SearchControl::unlimited());
+        let tt = Arc::new(TranspositionTable::new(1));
+        let best_move = crate::best_move(
+            &mut game,
+            3,
+            &[],
+            -Score::INFINITY,
+            Score::INFINITY,
+            &control,
+            &tt,
+        )
+        .unwrap();
+        let tt_entry = tt.probe(game.hash).unwrap();
+        assert_eq!(tt_entry.best_move, Some(best_move.best_move));
+        assert_eq!(tt_entry.depth, 3);
+        // searching again with the filled table has to agree with the fresh search
+        let again = crate::best_move(
+            &mut game,
+            3,
+            &[],
+            -Score::INFINITY,
+            Score::INFINITY,
+            &control,
+            &tt,
+        )
+        .unwrap();
+        assert_eq!(again.best_move, best_move.best_move);
+        assert_eq!(again.score, best_move.score);
+    }
+    #[test]
     fn test_see() {
         let mut game = Game::new();
-        game.create_piece(0, 5, 0);
-        game.create_piece(1, 5, 2);
-        game.create_piece(1, 0, 55);
-        game.create_piece(0, 3, 46);
-        game.create_piece(0, 0, 37);
+        game.create_piece(Color::White, Piece::King, Square::A1);
+        game.create_piece(Color::Black, Piece::King, Square::C1);
+        game.create_piece(Color::Black, Piece::Pawn, Square::H7);
+        game.create_piece(Color::White, Piece::Rook, Square::G6);
+        game.create_piece(Color::White, Piece::Pawn, Square::F5);
         game.set_moves();
-        assert!(
-            (crate::see(
+        assert_eq!(
+            crate::see(
                 &mut game,
                 &PieceMove {
-                    start: 55,
-                    end: 46,
+                    start: Square::H7,
+                    end: Square::G6,
                     special: SpecialMove::None,
-                }
-            ) - -4.0)
-                .abs()
-                < 0.5
+                },
+                &crate::PIECE_SCORES
+            ),
+            Score(400)
+        );
+    }
+    #[test]
+    fn test_move_ordering() {
+        let mut game = Game::from_fen("7k/8/8/1p1r4/8/2N5/8/K2Q4 w - - 0 1").unwrap();
+        let [king_move, knight_takes_rook, queen_takes_rook, knight_takes_pawn, killer] =
+            ["a1b1", "c3d5", "d1d5", "c3b5", "d1d2"].map(|uci| game.parse_uci_move(uci).unwrap());
+        let mut state = SearchState::default();
+        state.record_cutoff(Color::White, 3, killer, 2);
+        let moves = crate::moveutil::ordered_moves(&game, Color::White, Some(king_move), &state, 3);
+        assert_eq!(
+            moves[..5],
+            [
+                king_move,
+                knight_takes_rook,
+                queen_takes_rook,
+                knight_takes_pawn,
+                killer
+            ]
         );
+        // killers belong to their ply, but the history still puts the move ahead of other quiets
+        let moves = crate::moveutil::ordered_moves(&game, Color::White, None, &state, 4);
+        assert_eq!(moves[3], killer);
+        assert!(crate::moveutil::is_quiet(&game, Color::White, &killer));
+        assert!(!crate::moveutil::is_quiet(
+            &game,
+            Color::White,
+            &knight_takes_pawn
+        ));
+        // a promotion changes the material like a capture does, so it never becomes a killer
+        let mut game = Game::from_fen("7k/P7/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        let promotion = game.parse_uci_move("a7a8q").unwrap();
+        assert!(!crate::moveutil::is_quiet(&game, Color::White, &promotion));
     }
 }
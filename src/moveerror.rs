@@ -0,0 +1,23 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MoveError {
+    BadFormat(String),
+    Illegal(String),
+    Ambiguous(String),
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveError::BadFormat(input) => write!(f, "\"{}\" is not a move", input),
+            MoveError::Illegal(input) => write!(f, "{} is not legal in this position", input),
+            MoveError::Ambiguous(input) => {
+                write!(f, "{} could be more than one move in this position", input)
+            }
+        }
+    }
+}
+
+impl Error for MoveError {}
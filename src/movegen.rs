@@ -1,7 +1,10 @@
+use crate::color::Color;
 use crate::magics;
 use crate::moveutil;
+use crate::piece::Piece;
 use crate::piecemove::PieceMove;
 use crate::specialmove::SpecialMove;
+use crate::square::Square;
 use tinyvec::ArrayVec;
 
 #[derive(Clone, PartialEq)]
@@ -15,6 +18,12 @@ pub struct MoveGen {
     king_moves: [u64; 64],
 }
 
+impl Default for MoveGen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MoveGen {
     pub fn new() -> MoveGen {
         let mut knight_moves = [0; 64];
@@ -32,7 +41,7 @@ impl MoveGen {
             ];
             for possible_knight_move in &possible_knight_moves {
                 let possible_knight_move = *possible_knight_move;
-                if possible_knight_move >= 0 && possible_knight_move < 64 {
+                if (0..64).contains(&possible_knight_move) {
                     //i have no idea how this works anymore
                     if !((idx - 1) % 8 == 0 && (possible_knight_move + 1) % 8 == 0)
                         && !(idx % 8 == 0
@@ -64,12 +73,11 @@ impl MoveGen {
             ];
             for possible_king_move in &possible_king_moves {
                 let possible_king_move = *possible_king_move;
-                if possible_king_move >= 0 && possible_king_move < 64 {
-                    if !(idx % 8 == 0 && (possible_king_move + 1) % 8 == 0)
-                        && !((idx + 1) % 8 == 0 && possible_king_move % 8 == 0)
-                    {
-                        *king_move |= 1u64 << possible_king_move as u64;
-                    }
+                if (0..64).contains(&possible_king_move)
+                    && !(idx % 8 == 0 && (possible_king_move + 1) % 8 == 0)
+                    && !((idx + 1) % 8 == 0 && possible_king_move % 8 == 0)
+                {
+                    *king_move |= 1u64 << possible_king_move as u64;
                 }
             }
         }
@@ -209,23 +217,33 @@ impl MoveGen {
     }
     pub fn gen_move(
         &self,
-        color: u8,
-        piece: u8,
-        position: u8,
+        color: Color,
+        piece: Piece,
+        position: Square,
         blockers: u64,
         castle_available: [bool; 4],
+        en_passant: Option<Square>,
     ) -> ArrayVec<[PieceMove; 28]> {
+        let position = u8::from(position);
         match piece {
-            0 => self.gen_pawn(color, position, blockers),
-            1 => moveutil::bitboard_to_piecemoves(self.knight_moves[position as usize], position),
-            2 => self.gen_bishop(position, blockers),
-            3 => self.gen_rook(position, blockers),
-            4 => self.gen_queen(position, blockers),
-            5 => self.gen_king(color, position, blockers, castle_available),
-            _ => ArrayVec::new(),
+            Piece::Pawn => self.gen_pawn(color, position, blockers, en_passant),
+            Piece::Knight => moveutil::bitboard_to_piecemoves(
+                self.knight_moves[position as usize],
+                Square::new(position),
+            ),
+            Piece::Bishop => self.gen_bishop(position, blockers),
+            Piece::Rook => self.gen_rook(position, blockers),
+            Piece::Queen => self.gen_queen(position, blockers),
+            Piece::King => self.gen_king(color, position, blockers, castle_available),
         }
     }
-    fn gen_pawn(&self, color: u8, position: u8, blockers: u64) -> ArrayVec<[PieceMove; 28]> {
+    fn gen_pawn(
+        &self,
+        color: Color,
+        position: u8,
+        blockers: u64,
+        en_passant: Option<Square>,
+    ) -> ArrayVec<[PieceMove; 28]> {
         let position = position as isize;
         let mut square_moves = ArrayVec::new();
         let pos_change = -((color as isize * 2 - 1) * 8);
@@ -240,31 +258,29 @@ impl MoveGen {
                 ];
                 for promotion in &promotions {
                     square_moves.push(PieceMove {
-                        start,
-                        end,
+                        start: Square::new(start),
+                        end: Square::new(end),
                         special: *promotion,
                     });
                 }
             } else {
                 square_moves.push(PieceMove {
-                    start,
-                    end,
+                    start: Square::new(start),
+                    end: Square::new(end),
                     special: SpecialMove::None,
                 });
             }
         };
         if ((blockers >> (position + pos_change)) & 1) == 0 {
             add_move(position as u8, (position + pos_change) as u8);
-            if color == 0 {
-                if position >= 8
-                    && position < 16
+            if color == Color::White {
+                if (8..16).contains(&position)
                     && ((blockers >> (position + 2 * pos_change)) & 1) == 0
                 {
                     add_move(position as u8, (position + 2 * pos_change) as u8);
                 }
             } else {
-                if position >= 48
-                    && position < 56
+                if (48..56).contains(&position)
                     && ((blockers >> (position + 2 * pos_change)) & 1) == 0
                 {
                     add_move(position as u8, (position + 2 * pos_change) as u8);
@@ -278,13 +294,28 @@ impl MoveGen {
         {
             add_move(position as u8, (position + pos_change + 1) as u8);
         }
-        if position + pos_change - 1 >= 0
+        if position + pos_change > 0
             && position + pos_change - 1 < 64
             && ((blockers >> (position + pos_change - 1)) & 1) != 0
             && position % 8 != 0
         {
             add_move(position as u8, (position + pos_change - 1) as u8);
         }
+        if let Some(en_passant) = en_passant {
+            let en_passant = en_passant.index() as isize;
+            // the target square is behind the pawn that just moved, so only the other side can take
+            let target_rank = if color == Color::White { 5 } else { 2 };
+            if en_passant / 8 == target_rank
+                && ((en_passant == position + pos_change + 1 && (position + 1) % 8 != 0)
+                    || (en_passant == position + pos_change - 1 && position % 8 != 0))
+            {
+                square_moves.push(PieceMove {
+                    start: Square::new(position as u8),
+                    end: Square::new(en_passant as u8),
+                    special: SpecialMove::EnPassant,
+                });
+            }
+        }
         square_moves
     }
     fn gen_bishop(&self, position: u8, blockers: u64) -> ArrayVec<[PieceMove; 28]> {
@@ -292,14 +323,14 @@ impl MoveGen {
         let key = (blockers.wrapping_mul(magics::BISHOP_MAGICS[position as usize]))
             >> (64 - magics::BISHOP_INDICES[position as usize]);
         let moves = self.bishop_table[position as usize * 512 + key as usize];
-        moveutil::bitboard_to_piecemoves(moves, position)
+        moveutil::bitboard_to_piecemoves(moves, Square::new(position))
     }
     fn gen_rook(&self, position: u8, blockers: u64) -> ArrayVec<[PieceMove; 28]> {
         let blockers = blockers & self.rook_masks[position as usize];
         let key = (blockers.wrapping_mul(magics::ROOK_MAGICS[position as usize]))
             >> (64 - magics::ROOK_INDICES[position as usize]);
         let moves = self.rook_table[position as usize * 4096 + key as usize];
-        moveutil::bitboard_to_piecemoves(moves, position)
+        moveutil::bitboard_to_piecemoves(moves, Square::new(position))
     }
     fn gen_bishop_classical(rays: [[u64; 8]; 64], position: u8, blockers: u64) -> u64 {
         let mut board = 0;
@@ -379,34 +410,36 @@ impl MoveGen {
             };
             board |= moves;
         }
-        moveutil::bitboard_to_piecemoves(board, position)
+        moveutil::bitboard_to_piecemoves(board, Square::new(position))
     }
     fn gen_king(
         &self,
-        color: u8,
+        color: Color,
         position: u8,
         blockers: u64,
         castle_available: [bool; 4],
     ) -> ArrayVec<[PieceMove; 28]> {
-        let mut square_moves =
-            moveutil::bitboard_to_piecemoves(self.king_moves[position as usize], position);
-        if castle_available[(color * 2) as usize]
+        let mut square_moves = moveutil::bitboard_to_piecemoves(
+            self.king_moves[position as usize],
+            Square::new(position),
+        );
+        if castle_available[color as usize * 2]
             && ((blockers >> (position + 1)) & 1) == 0
             && ((blockers >> (position + 2)) & 1) == 0
         {
             square_moves.push(PieceMove {
-                start: position,
-                end: position + 2,
+                start: Square::new(position),
+                end: Square::new(position + 2),
                 special: SpecialMove::CastleKingside,
             });
         }
-        if castle_available[(color * 2 + 1) as usize]
+        if castle_available[color as usize * 2 + 1]
             && ((blockers >> (position - 1)) & 1) == 0
             && ((blockers >> (position - 2)) & 1) == 0
         {
             square_moves.push(PieceMove {
-                start: position,
-                end: position - 2,
+                start: Square::new(position),
+                end: Square::new(position - 2),
                 special: SpecialMove::CastleQueenside,
             });
         }
@@ -1,23 +1,32 @@
+use crate::color::Color;
 use crate::game::Game;
+use crate::piece::Piece;
 use crate::piecemove::PieceMove;
+use crate::searchstate::SearchState;
 use crate::specialmove::SpecialMove;
+use crate::square::Square;
+use std::cmp::Reverse;
 use tinyvec::ArrayVec;
 
-pub fn legal_move(game: &mut Game, color: u8, piece: u8, piece_move: &PieceMove) -> bool {
-    if game.square_exists[piece_move.end as usize]
-        && game.square_to_color[piece_move.end as usize] == color
+pub fn legal_move(game: &mut Game, color: Color, piece: Piece, piece_move: &PieceMove) -> bool {
+    let start = piece_move.start.index();
+    if game.square_exists[piece_move.end.index()]
+        && game.square_to_color[piece_move.end.index()] == color
     {
         return false;
     }
+    let is_castle = piece_move.special == SpecialMove::CastleKingside
+        || piece_move.special == SpecialMove::CastleQueenside;
+    if is_castle && game.in_check(color) {
+        return false;
+    }
     if piece_move.special == SpecialMove::CastleKingside {
-        if game.square_exists[piece_move.start as usize + 1]
-            || game.square_exists[piece_move.start as usize + 2]
-        {
+        if game.square_exists[start + 1] || game.square_exists[start + 2] {
             return false;
         }
         let mid_castle_move = PieceMove {
             start: piece_move.start,
-            end: piece_move.start + 1,
+            end: Square::new(start as u8 + 1),
             special: SpecialMove::None,
         };
         let prev_game_state = game.make_move(color, piece, &mid_castle_move);
@@ -27,15 +36,15 @@ pub fn legal_move(game: &mut Game, color: u8, piece: u8, piece_move: &PieceMove)
         }
         game.unmake_move(color, piece, &mid_castle_move, &prev_game_state);
     } else if piece_move.special == SpecialMove::CastleQueenside {
-        if game.square_exists[piece_move.start as usize - 1]
-            || game.square_exists[piece_move.start as usize - 2]
-            || game.square_exists[piece_move.start as usize - 3]
+        if game.square_exists[start - 1]
+            || game.square_exists[start - 2]
+            || game.square_exists[start - 3]
         {
             return false;
         }
         let mid_castle_move = PieceMove {
             start: piece_move.start,
-            end: piece_move.start - 1,
+            end: Square::new(start as u8 - 1),
             special: SpecialMove::None,
         };
         let prev_game_state = game.make_move(color, piece, &mid_castle_move);
@@ -54,13 +63,73 @@ pub fn legal_move(game: &mut Game, color: u8, piece: u8, piece_move: &PieceMove)
     true
 }
 
-pub fn bitboard_to_piecemoves(board: u64, start: u8) -> ArrayVec<[PieceMove; 28]> {
+// sort keys, far enough apart that no history score reaches the killers
+const TT_MOVE_KEY: i32 = i32::MAX;
+const CAPTURE_KEY: i32 = 1 << 22;
+const KILLER_KEY: i32 = 1 << 21;
+
+// Pseudo-legal moves for color in the order the search should try them: the transposition table
+// move, captures by most valuable victim then least valuable attacker, the killers at ply, then
+// quiet moves by history.
+pub fn ordered_moves(
+    game: &Game,
+    color: Color,
+    tt_move: Option<PieceMove>,
+    state: &SearchState,
+    ply: usize,
+) -> Vec<PieceMove> {
+    let mut moves = Vec::new();
+    for square in 0..64 {
+        if !game.square_exists[square] || game.square_to_color[square] != color {
+            continue;
+        }
+        moves.extend_from_slice(&game.square_moves[square]);
+    }
+    let killers = state.killers(ply);
+    // stable, so ties stay in generation order
+    moves.sort_by_key(|piece_move| {
+        let key = if Some(*piece_move) == tt_move {
+            TT_MOVE_KEY
+        } else if let Some(victim) = captured_piece(game, color, piece_move) {
+            let attacker = game.square_to_piece[piece_move.start.index()];
+            CAPTURE_KEY + victim.index() as i32 * 8 - attacker.index() as i32
+        } else if killers[0] == Some(*piece_move) {
+            KILLER_KEY + 1
+        } else if killers[1] == Some(*piece_move) {
+            KILLER_KEY
+        } else {
+            state.history(color, piece_move)
+        };
+        Reverse(key)
+    });
+    moves
+}
+
+// what piece_move by color takes, if anything
+pub fn captured_piece(game: &Game, color: Color, piece_move: &PieceMove) -> Option<Piece> {
+    let end = piece_move.end.index();
+    if piece_move.special == SpecialMove::EnPassant {
+        Some(Piece::Pawn)
+    } else if game.square_exists[end] && game.square_to_color[end] != color {
+        Some(game.square_to_piece[end])
+    } else {
+        None
+    }
+}
+
+// neither a capture nor a promotion, the moves killers and history are kept for
+pub fn is_quiet(game: &Game, color: Color, piece_move: &PieceMove) -> bool {
+    captured_piece(game, color, piece_move).is_none()
+        && piece_move.special.promotion_piece().is_none()
+}
+
+pub fn bitboard_to_piecemoves(board: u64, start: Square) -> ArrayVec<[PieceMove; 28]> {
     let mut square_moves = ArrayVec::new();
     for i in 0..64 {
         if ((board >> i) & 1) != 0 {
             square_moves.push(PieceMove {
                 start,
-                end: i,
+                end: Square::new(i),
                 special: SpecialMove::None,
             });
         }
@@ -71,7 +140,7 @@ pub fn bitboard_to_piecemoves(board: u64, start: u8) -> ArrayVec<[PieceMove; 28]
 pub fn piecemoves_to_bitboard(piece_moves: ArrayVec<[PieceMove; 28]>) -> u64 {
     let mut bitboard = 0;
     for move_idx in 0..piece_moves.len() {
-        bitboard |= 1 << piece_moves[move_idx as usize].end;
+        bitboard |= piece_moves[move_idx].end.bitboard();
     }
     bitboard
 }
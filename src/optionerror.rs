@@ -0,0 +1,21 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OptionError {
+    UnknownOption(String),
+    BadValue { name: String, value: String },
+}
+
+impl fmt::Display for OptionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OptionError::UnknownOption(name) => write!(f, "no option named \"{}\"", name),
+            OptionError::BadValue { name, value } => {
+                write!(f, "\"{}\" is not a valid value for {}", value, name)
+            }
+        }
+    }
+}
+
+impl Error for OptionError {}
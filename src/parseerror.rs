@@ -0,0 +1,21 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseError {
+    BadColor(String),
+    BadPiece(String),
+    BadSquare(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::BadColor(input) => write!(f, "\"{}\" is not a color", input),
+            ParseError::BadPiece(input) => write!(f, "\"{}\" is not a piece", input),
+            ParseError::BadSquare(input) => write!(f, "\"{}\" is not a square", input),
+        }
+    }
+}
+
+impl Error for ParseError {}
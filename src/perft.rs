@@ -0,0 +1,34 @@
+use crate::game::Game;
+use crate::piecemove::PieceMove;
+
+pub fn perft(game: &mut Game, depth: u8) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = game.legal_moves();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    let mut nodes = 0;
+    for piece_move in moves {
+        let prev_game_state = game.play_move(&piece_move);
+        nodes += perft(game, depth - 1);
+        game.undo_move(&piece_move, &prev_game_state);
+    }
+    nodes
+}
+
+// node count below each legal move, for finding the move that disagrees with a reference engine
+pub fn divide(game: &mut Game, depth: u8) -> Vec<(PieceMove, u64)> {
+    let mut divided = Vec::new();
+    if depth == 0 {
+        return divided;
+    }
+    for piece_move in game.legal_moves() {
+        let prev_game_state = game.play_move(&piece_move);
+        let nodes = perft(game, depth - 1);
+        game.undo_move(&piece_move, &prev_game_state);
+        divided.push((piece_move, nodes));
+    }
+    divided
+}
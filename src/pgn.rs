@@ -0,0 +1,630 @@
+use crate::color::Color;
+use crate::game::Game;
+use crate::pgnerror::PgnError;
+use crate::pgngame::PgnGame;
+use crate::pgnmove::PgnMove;
+use crate::piecemove::PieceMove;
+use std::iter::Peekable;
+use std::str::Chars;
+
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+// the export format asks for movetext lines of at most 80 characters
+const LINE_LENGTH: usize = 80;
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    OpenVariation,
+    CloseVariation,
+    Nag(u8),
+    MoveNumber,
+    Result(String),
+    San(String),
+}
+
+impl PgnGame {
+    // the first game in the text
+    pub fn from_pgn(pgn: &str) -> Result<PgnGame, PgnError> {
+        PgnGame::all_from_pgn(pgn)?
+            .into_iter()
+            .next()
+            .ok_or(PgnError::NoGame)
+    }
+    // every game in a database file
+    pub fn all_from_pgn(pgn: &str) -> Result<Vec<PgnGame>, PgnError> {
+        let tokens = tokenize(pgn)?;
+        let mut idx = 0;
+        let mut pgn_games = Vec::new();
+        while idx < tokens.len() {
+            let mut pgn_game = PgnGame::new();
+            while let Some(Token::Tag(name, value)) = tokens.get(idx) {
+                pgn_game.set_tag(name, value);
+                idx += 1;
+            }
+            let mut game = pgn_game.start_position()?;
+            pgn_game.moves = parse_line(&tokens, &mut idx, &mut game, 0, &mut pgn_game.comment)?;
+            if let Some(Token::Result(result)) = tokens.get(idx) {
+                if pgn_game.tag("Result").is_none() {
+                    pgn_game.set_tag("Result", result);
+                }
+                idx += 1;
+            }
+            pgn_games.push(pgn_game);
+        }
+        Ok(pgn_games)
+    }
+    // a finished or unfinished engine game, tagged with its start position and result
+    pub fn from_moves(start: &Game, moves: &[PieceMove]) -> PgnGame {
+        let mut pgn_game = PgnGame::new();
+        let fen = start.to_fen();
+        if fen != START_FEN {
+            pgn_game.set_tag("SetUp", "1");
+            pgn_game.set_tag("FEN", &fen);
+        }
+        let mut game = start.clone();
+        for piece_move in moves {
+            game.play_move(piece_move);
+            pgn_game.moves.push(PgnMove::new(*piece_move));
+        }
+        let result = if game.in_checkmate(game.side_to_move) {
+            if game.side_to_move == Color::White {
+                "0-1"
+            } else {
+                "1-0"
+            }
+        } else if game.game_over() {
+            "1/2-1/2"
+        } else {
+            "*"
+        };
+        pgn_game.set_tag("Result", result);
+        pgn_game
+    }
+    pub fn start_position(&self) -> Result<Game, PgnError> {
+        match self.tag("FEN") {
+            Some(fen) => Game::from_fen(fen).map_err(PgnError::BadFen),
+            None => {
+                let mut game = Game::new();
+                game.starting_game();
+                Ok(game)
+            }
+        }
+    }
+    // the position at the end of the main line
+    pub fn end_position(&self) -> Result<Game, PgnError> {
+        let mut game = self.start_position()?;
+        for pgn_move in &self.moves {
+            game.play_move(&pgn_move.piece_move);
+        }
+        Ok(game)
+    }
+    pub fn to_pgn(&self) -> Result<String, PgnError> {
+        let mut pgn = String::new();
+        for (name, default) in SEVEN_TAG_ROSTER.iter() {
+            let value = self.tag(name).unwrap_or(default);
+            pgn.push_str(&format!("[{} \"{}\"]\n", name, escape(value)));
+        }
+        for (name, value) in &self.tags {
+            if SEVEN_TAG_ROSTER
+                .iter()
+                .all(|(roster_name, _)| roster_name != name)
+            {
+                pgn.push_str(&format!("[{} \"{}\"]\n", name, escape(value)));
+            }
+        }
+        pgn.push('\n');
+        let mut game = self.start_position()?;
+        let mut movetext = Vec::new();
+        if let Some(comment) = &self.comment {
+            movetext.push(format!("{{{}}}", comment));
+        }
+        write_line(&mut movetext, &mut game, &self.moves);
+        movetext.push(self.result().to_owned());
+
+        let mut line = String::new();
+        for word in movetext {
+            let joined = line.is_empty() || line.ends_with('(') || word == ")";
+            if !line.is_empty() && line.len() + word.len() + !joined as usize > LINE_LENGTH {
+                pgn.push_str(&line);
+                pgn.push('\n');
+                line.clear();
+            } else if !joined {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        pgn.push_str(&line);
+        pgn.push('\n');
+        Ok(pgn)
+    }
+}
+
+// moves up to the end of the line, game is left at the end of the main line and restored for
+// variations, and empty_comment gets the comments of a line without moves
+fn parse_line(
+    tokens: &[Token],
+    idx: &mut usize,
+    game: &mut Game,
+    depth: usize,
+    empty_comment: &mut Option<String>,
+) -> Result<Vec<PgnMove>, PgnError> {
+    let mut moves: Vec<PgnMove> = Vec::new();
+    let mut prev_game_states = Vec::new();
+    // comments go after the last move unless a variation came in between, then before the next
+    let mut comment_before: Option<String> = None;
+    let mut after_variation = false;
+    loop {
+        match tokens.get(*idx) {
+            Some(Token::MoveNumber) => (),
+            Some(Token::San(san)) => {
+                let piece_move = game
+                    .parse_san_move(san)
+                    .map_err(|error| PgnError::BadMove {
+                        ply: game.hash_history.len() + 1,
+                        error,
+                    })?;
+                prev_game_states.push(game.play_move(&piece_move));
+                let mut pgn_move = PgnMove::new(piece_move);
+                pgn_move.comment_before = comment_before.take();
+                moves.push(pgn_move);
+                after_variation = false;
+            }
+            Some(Token::Nag(nag)) => match moves.last_mut() {
+                Some(pgn_move) => pgn_move.nags.push(*nag),
+                None => return Err(PgnError::UnexpectedToken(format!("${}", nag))),
+            },
+            Some(Token::Comment(comment)) => match moves.last_mut() {
+                Some(pgn_move) if comment_before.is_none() && !after_variation => {
+                    add_comment(&mut pgn_move.comment, comment)
+                }
+                _ => add_comment(&mut comment_before, comment),
+            },
+            Some(Token::OpenVariation) => {
+                let (last_move, prev_game_state) = match (moves.last_mut(), prev_game_states.pop())
+                {
+                    (Some(last_move), Some(prev_game_state)) => (last_move, prev_game_state),
+                    _ => return Err(PgnError::VariationWithoutMove),
+                };
+                game.undo_move(&last_move.piece_move, &prev_game_state);
+                *idx += 1;
+                let variation = parse_line(tokens, idx, game, depth + 1, &mut last_move.comment)?;
+                last_move.variations.push(variation);
+                prev_game_states.push(game.play_move(&last_move.piece_move));
+                after_variation = true;
+            }
+            Some(Token::CloseVariation) if depth > 0 => break,
+            Some(Token::CloseVariation) => {
+                return Err(PgnError::UnexpectedToken(")".to_owned()));
+            }
+            Some(Token::Result(result)) if depth > 0 => {
+                return Err(PgnError::UnexpectedToken(result.clone()));
+            }
+            None if depth > 0 => return Err(PgnError::UnclosedVariation),
+            Some(Token::Tag(..)) if depth > 0 => return Err(PgnError::UnclosedVariation),
+            Some(Token::Result(_)) | Some(Token::Tag(..)) | None => break,
+        }
+        *idx += 1;
+    }
+    // a comment at the very end of a line still belongs to its last move, if it has one, even
+    // when that means writing it back before the move's variations
+    if let Some(comment) = comment_before {
+        match moves.last_mut() {
+            Some(last_move) => add_comment(&mut last_move.comment, &comment),
+            None => add_comment(empty_comment, &comment),
+        }
+    }
+    if depth == 0 {
+        return Ok(moves);
+    }
+    for (pgn_move, prev_game_state) in moves.iter().zip(prev_game_states.iter()).rev() {
+        game.undo_move(&pgn_move.piece_move, prev_game_state);
+    }
+    Ok(moves)
+}
+
+fn write_line(movetext: &mut Vec<String>, game: &mut Game, moves: &[PgnMove]) {
+    let mut prev_game_states = Vec::new();
+    // black's moves only get a number at the start of a line or after an interruption
+    let mut needs_number = true;
+    for pgn_move in moves {
+        if let Some(comment) = &pgn_move.comment_before {
+            movetext.push(format!("{{{}}}", comment));
+            needs_number = true;
+        }
+        if game.side_to_move == Color::White {
+            movetext.push(format!("{}.", game.fullmove_number));
+        } else if needs_number {
+            movetext.push(format!("{}...", game.fullmove_number));
+        }
+        movetext.push(game.move_to_san(&pgn_move.piece_move));
+        for nag in &pgn_move.nags {
+            movetext.push(format!("${}", nag));
+        }
+        needs_number = false;
+        if let Some(comment) = &pgn_move.comment {
+            movetext.push(format!("{{{}}}", comment));
+            needs_number = true;
+        }
+        for variation in &pgn_move.variations {
+            movetext.push("(".to_owned());
+            write_line(movetext, game, variation);
+            movetext.push(")".to_owned());
+            needs_number = true;
+        }
+        prev_game_states.push(game.play_move(&pgn_move.piece_move));
+    }
+    for (pgn_move, prev_game_state) in moves.iter().zip(prev_game_states.iter()).rev() {
+        game.undo_move(&pgn_move.piece_move, prev_game_state);
+    }
+}
+
+fn add_comment(target: &mut Option<String>, comment: &str) {
+    match target {
+        Some(existing) => {
+            existing.push(' ');
+            existing.push_str(comment);
+        }
+        None => *target = Some(comment.to_owned()),
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn tokenize(pgn: &str) -> Result<Vec<Token>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut chars = pgn.chars().peekable();
+    let mut line_start = true;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => {
+                line_start = true;
+                continue;
+            }
+            // escape mechanism for lines other programs put in, and ; comments to the end of the line
+            '%' if line_start => {
+                skip_line(&mut chars);
+                continue;
+            }
+            ';' => {
+                let comment: String = chars.by_ref().take_while(|c| *c != '\n').collect();
+                tokens.push(Token::Comment(comment.trim().to_owned()));
+                line_start = true;
+                continue;
+            }
+            c if c.is_whitespace() => (),
+            '[' => tokens.push(read_tag(&mut chars)?),
+            '{' => {
+                let mut comment = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => comment.push(c),
+                        None => return Err(PgnError::UnterminatedComment),
+                    }
+                }
+                let comment: Vec<&str> = comment.split_whitespace().collect();
+                tokens.push(Token::Comment(comment.join(" ")));
+            }
+            '(' => tokens.push(Token::OpenVariation),
+            ')' => tokens.push(Token::CloseVariation),
+            '$' => {
+                let nag = read_while(&mut chars, String::new(), |c| c.is_ascii_digit());
+                match nag.parse() {
+                    Ok(nag) => tokens.push(Token::Nag(nag)),
+                    Err(_) => return Err(PgnError::UnexpectedToken(format!("${}", nag))),
+                }
+            }
+            '!' | '?' => {
+                let glyph = read_while(&mut chars, c.to_string(), |c| c == '!' || c == '?');
+                let nag = match glyph.as_str() {
+                    "!" => 1,
+                    "?" => 2,
+                    "!!" => 3,
+                    "??" => 4,
+                    "!?" => 5,
+                    "?!" => 6,
+                    _ => return Err(PgnError::UnexpectedToken(glyph)),
+                };
+                tokens.push(Token::Nag(nag));
+            }
+            _ => {
+                let symbol = read_while(&mut chars, c.to_string(), |c| {
+                    c.is_ascii_alphanumeric() || "+#=:-/*_.".contains(c)
+                });
+                match symbol.as_str() {
+                    "1-0" | "0-1" | "1/2-1/2" | "*" => tokens.push(Token::Result(symbol)),
+                    _ => {
+                        // move numbers are often written right against the move, as in 1.e4
+                        let san = symbol.trim_start_matches(|c: char| c.is_ascii_digit());
+                        let san = if san.starts_with('.') {
+                            tokens.push(Token::MoveNumber);
+                            san.trim_start_matches('.')
+                        } else {
+                            &symbol
+                        };
+                        if !san.is_empty() {
+                            tokens.push(Token::San(san.to_owned()));
+                        }
+                    }
+                }
+            }
+        }
+        line_start = false;
+    }
+    Ok(tokens)
+}
+
+fn read_tag(chars: &mut Peekable<Chars>) -> Result<Token, PgnError> {
+    let mut tag = String::new();
+    let mut value = String::new();
+    let mut in_value = false;
+    loop {
+        match chars.next() {
+            Some('"') if !in_value => {
+                in_value = true;
+                tag.push('"');
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            if let Some(escaped) = chars.next() {
+                                value.push(escaped);
+                            }
+                        }
+                        Some(c) => value.push(c),
+                        None => return Err(PgnError::BadTag(tag)),
+                    }
+                }
+                tag.push_str(&value);
+                tag.push('"');
+            }
+            Some(']') if in_value => break,
+            Some('\n') | None => return Err(PgnError::BadTag(tag)),
+            Some(c) if in_value && !c.is_whitespace() => {
+                tag.push(c);
+                return Err(PgnError::BadTag(tag));
+            }
+            Some(c) => tag.push(c),
+        }
+    }
+    let name = tag[..tag.find('"').unwrap_or(0)].trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(PgnError::BadTag(tag));
+    }
+    Ok(Token::Tag(name.to_owned(), value))
+}
+
+fn read_while(
+    chars: &mut Peekable<Chars>,
+    mut read: String,
+    predicate: impl Fn(char) -> bool,
+) -> String {
+    while let Some(c) = chars.peek() {
+        if !predicate(*c) {
+            break;
+        }
+        read.push(*c);
+        chars.next();
+    }
+    read
+}
+
+fn skip_line(chars: &mut Peekable<Chars>) {
+    for c in chars {
+        if c == '\n' {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::Game;
+    use crate::moveerror::MoveError;
+    use crate::pgnerror::PgnError;
+    use crate::pgngame::PgnGame;
+
+    const SAMPLE: &str = r#"[Event "F/S Return Match"]
+[Site "Belgrade, Serbia JUG"]
+[Date "1992.11.04"]
+[Round "29"]
+[White "Fischer, Robert J."]
+[Black "Spassky, Boris V."]
+[Result "1/2-1/2"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bb5 {This opening is called the Ruy Lopez.} 3... a6
+4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3 O-O 9. h3 Nb8 10. d4 Nbd7
+11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 14. Bg5 b4 15. Nb1 h6 16. Bh4 c5 17. dxe5
+Nxe4 18. Bxe7 Qxe7 19. exd6 Qf6 20. Nbd2 Nxd6 21. Nc4 Nxc4 22. Bxc4 Nb6
+23. Ne5 Rae8 24. Bxf7+ Rxf7 25. Nxf7 Rxe1+ 26. Qxe1 Kxf7 27. Qe3 Qg5 28. Qxg5
+hxg5 29. b3 Ke6 30. a3 Kd6 31. axb4 cxb4 32. Ra5 Nd5 33. f3 Bc8 34. Kf2 Bf5
+35. Ra7 g6 36. Ra6+ Kc5 37. Ke1 Nf4 38. g3 Nxh3 39. Kd2 Kb5 40. Rd6 Kc5 41. Ra6
+Nf2 42. g4 Bd3 43. Re6 1/2-1/2
+"#;
+
+    #[test]
+    fn test_read_pgn() {
+        let pgn_game = PgnGame::from_pgn(SAMPLE).unwrap();
+        assert_eq!(pgn_game.tag("White"), Some("Fischer, Robert J."));
+        assert_eq!(pgn_game.result(), "1/2-1/2");
+        assert_eq!(pgn_game.moves.len(), 85);
+        assert_eq!(
+            pgn_game.moves[4].comment.as_deref(),
+            Some("This opening is called the Ruy Lopez.")
+        );
+        assert_eq!(
+            pgn_game.end_position().unwrap().to_fen(),
+            "8/8/4R1p1/2k3p1/1p4P1/1P1b1P2/3K1n2/8 b - - 2 43"
+        );
+    }
+
+    #[test]
+    fn test_read_annotations() {
+        let pgn = "{Opening} 1.e4!? e5 $1 {Solid} (1...c5 2.Nf3 (2.Nc3) d6 {Najdorf next}) \
+                   (1...e6?!) 2.Nf3 ; the usual\n% skipped line\n2...Nc6 *";
+        let pgn_game = PgnGame::from_pgn(pgn).unwrap();
+        assert_eq!(pgn_game.moves.len(), 4);
+        assert_eq!(pgn_game.moves[0].comment_before.as_deref(), Some("Opening"));
+        assert_eq!(pgn_game.moves[0].nags, vec![5]);
+        assert_eq!(pgn_game.moves[1].nags, vec![1]);
+        assert_eq!(pgn_game.moves[1].comment.as_deref(), Some("Solid"));
+        assert_eq!(pgn_game.moves[1].variations.len(), 2);
+        let sicilian = &pgn_game.moves[1].variations[0];
+        assert_eq!(sicilian.len(), 3);
+        assert_eq!(sicilian[1].variations[0].len(), 1);
+        assert_eq!(sicilian[2].comment.as_deref(), Some("Najdorf next"));
+        assert_eq!(pgn_game.moves[1].variations[1][0].nags, vec![6]);
+        assert_eq!(pgn_game.moves[2].comment.as_deref(), Some("the usual"));
+        assert_eq!(pgn_game.result(), "*");
+        assert_eq!(
+            pgn_game.end_position().unwrap().to_fen(),
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3"
+        );
+    }
+
+    #[test]
+    fn test_write_pgn() {
+        let pgn = "[Black \"Engine \\\"B\\\"\"]\n[Annotator \"me\"]\n\
+                   1. e4 {Best by test} e5 $1 (1... c5 2. Nf3 (2. Nc3) d6) 2. Nf3 *";
+        let pgn_game = PgnGame::from_pgn(pgn).unwrap();
+        let written = pgn_game.to_pgn().unwrap();
+        assert_eq!(
+            written,
+            "[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n\
+             [White \"?\"]\n[Black \"Engine \\\"B\\\"\"]\n[Result \"*\"]\n[Annotator \"me\"]\n\n\
+             1. e4 {Best by test} 1... e5 $1 (1... c5 2. Nf3 (2. Nc3) 2... d6) 2. Nf3 *\n"
+        );
+        let reread = PgnGame::from_pgn(&written).unwrap();
+        assert_eq!(reread.moves, pgn_game.moves);
+        assert_eq!(reread.tag("Black"), pgn_game.tag("Black"));
+
+        // long games wrap, and still read back the same
+        let fischer = PgnGame::from_pgn(super::tests::SAMPLE).unwrap();
+        let written = fischer.to_pgn().unwrap();
+        assert!(written.lines().all(|line| line.len() <= 80));
+        assert_eq!(PgnGame::from_pgn(&written).unwrap(), fischer);
+    }
+
+    #[test]
+    fn test_comment_order() {
+        // a comment after a variation comes before the next move, not after the one before it
+        let pgn = "1. e4 {Best by test} (1. d4) {Back to e4} 1... e5 (1... c5) {Or e5} 2. Nf3 *";
+        let pgn_game = PgnGame::from_pgn(pgn).unwrap();
+        assert_eq!(pgn_game.moves[0].comment.as_deref(), Some("Best by test"));
+        assert_eq!(
+            pgn_game.moves[1].comment_before.as_deref(),
+            Some("Back to e4")
+        );
+        assert_eq!(pgn_game.moves[2].comment_before.as_deref(), Some("Or e5"));
+        let written = pgn_game.to_pgn().unwrap();
+        assert!(written.ends_with(
+            "\n\n1. e4 {Best by test} (1. d4) {Back to e4} 1... e5 (1... c5) {Or e5} 2. Nf3 *\n"
+        ));
+        assert_eq!(PgnGame::from_pgn(&written).unwrap().moves, pgn_game.moves);
+
+        // with no moves to go with, the comment is the game's own
+        let pgn_game = PgnGame::from_pgn("{Adjourned before the first move} *").unwrap();
+        assert_eq!(
+            pgn_game.comment.as_deref(),
+            Some("Adjourned before the first move")
+        );
+        let written = pgn_game.to_pgn().unwrap();
+        assert!(written.ends_with("\n\n{Adjourned before the first move} *\n"));
+        assert_eq!(
+            PgnGame::from_pgn(&written).unwrap().comment,
+            pgn_game.comment
+        );
+    }
+
+    #[test]
+    fn test_from_moves() {
+        let mut game = Game::new();
+        game.starting_game();
+        let moves: Vec<_> = ["f2f3", "e7e5", "g2g4", "d8h4"]
+            .iter()
+            .map(|uci_move| {
+                let piece_move = game.parse_uci_move(uci_move).unwrap();
+                game.play_move(&piece_move);
+                piece_move
+            })
+            .collect();
+        let mut start = Game::new();
+        start.starting_game();
+        let pgn_game = PgnGame::from_moves(&start, &moves);
+        assert_eq!(pgn_game.result(), "0-1");
+        assert!(pgn_game
+            .to_pgn()
+            .unwrap()
+            .ends_with("\n\n1. f3 e5 2. g4 Qh4# 0-1\n"));
+
+        let start = Game::from_fen("4k3/8/8/8/8/8/8/4K2R b K - 0 30").unwrap();
+        let mut game = start.clone();
+        let piece_move = game.parse_uci_move("e8d7").unwrap();
+        let pgn_game = PgnGame::from_moves(&start, &[piece_move]);
+        assert_eq!(pgn_game.tag("FEN"), Some("4k3/8/8/8/8/8/8/4K2R b K - 0 30"));
+        assert!(pgn_game.to_pgn().unwrap().ends_with("\n\n30... Kd7 *\n"));
+    }
+
+    #[test]
+    fn test_read_multiple_games() {
+        let pgn = format!("{}\n[Event \"Second\"]\n\n1. d4 d5 0-1\n\n1. c4 *", SAMPLE);
+        let pgn_games = PgnGame::all_from_pgn(&pgn).unwrap();
+        assert_eq!(pgn_games.len(), 3);
+        assert_eq!(pgn_games[1].tag("Event"), Some("Second"));
+        assert_eq!(pgn_games[1].result(), "0-1");
+        assert_eq!(pgn_games[2].moves.len(), 1);
+        assert_eq!(PgnGame::all_from_pgn("  \n").unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn test_pgn_errors() {
+        assert_eq!(PgnGame::from_pgn(""), Err(PgnError::NoGame));
+        assert_eq!(
+            PgnGame::from_pgn("1. e4 e5 2. Ke3 *"),
+            Err(PgnError::BadMove {
+                ply: 3,
+                error: MoveError::Illegal("Ke3".to_owned())
+            })
+        );
+        assert_eq!(
+            PgnGame::from_pgn("1. e4 {unfinished"),
+            Err(PgnError::UnterminatedComment)
+        );
+        assert_eq!(
+            PgnGame::from_pgn("1. e4 (1. d4 *"),
+            Err(PgnError::UnexpectedToken("*".to_owned()))
+        );
+        assert_eq!(
+            PgnGame::from_pgn("1. e4 (1. d4"),
+            Err(PgnError::UnclosedVariation)
+        );
+        assert_eq!(
+            PgnGame::from_pgn("(1. d4) 1. e4"),
+            Err(PgnError::VariationWithoutMove)
+        );
+        assert_eq!(
+            PgnGame::from_pgn("1. e4 ) e5"),
+            Err(PgnError::UnexpectedToken(")".to_owned()))
+        );
+        assert!(matches!(
+            PgnGame::from_pgn("[Event \"x\" junk]\n1. e4"),
+            Err(PgnError::BadTag(_))
+        ));
+        assert!(matches!(
+            PgnGame::from_pgn("[FEN \"8/8/8/8/8/8/8/8 w - - 0 1\"]\n*"),
+            Err(PgnError::BadFen(_))
+        ));
+    }
+}
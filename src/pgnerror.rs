@@ -0,0 +1,35 @@
+use crate::fenerror::FenError;
+use crate::moveerror::MoveError;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PgnError {
+    BadTag(String),
+    UnterminatedComment,
+    BadFen(FenError),
+    BadMove { ply: usize, error: MoveError },
+    UnexpectedToken(String),
+    UnclosedVariation,
+    VariationWithoutMove,
+    NoGame,
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PgnError::BadTag(tag) => write!(f, "malformed tag pair \"{}\"", tag),
+            PgnError::UnterminatedComment => write!(f, "comment without a closing brace"),
+            PgnError::BadFen(err) => write!(f, "FEN tag: {}", err),
+            PgnError::BadMove { ply, error } => write!(f, "ply {}: {}", ply, error),
+            PgnError::UnexpectedToken(token) => write!(f, "unexpected \"{}\"", token),
+            PgnError::UnclosedVariation => write!(f, "variation without a closing parenthesis"),
+            PgnError::VariationWithoutMove => {
+                write!(f, "variation before any move it could replace")
+            }
+            PgnError::NoGame => write!(f, "no game found"),
+        }
+    }
+}
+
+impl Error for PgnError {}
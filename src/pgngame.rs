@@ -0,0 +1,32 @@
+use crate::pgnmove::PgnMove;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PgnGame {
+    // in the order they were read or set, the Seven Tag Roster is put first when writing
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<PgnMove>,
+    // a comment in a game without moves, which has no move to hang it on
+    pub comment: Option<String>,
+}
+
+impl PgnGame {
+    pub fn new() -> PgnGame {
+        PgnGame::default()
+    }
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag_name, _)| tag_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag_name, _)| tag_name == name) {
+            Some(tag) => tag.1 = value.to_owned(),
+            None => self.tags.push((name.to_owned(), value.to_owned())),
+        }
+    }
+    // 1-0, 0-1, 1/2-1/2 or * for unfinished games
+    pub fn result(&self) -> &str {
+        self.tag("Result").unwrap_or("*")
+    }
+}
@@ -0,0 +1,24 @@
+use crate::piecemove::PieceMove;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PgnMove {
+    pub piece_move: PieceMove,
+    pub nags: Vec<u8>,
+    // comment written before the move, mostly seen at the start of a game or variation
+    pub comment_before: Option<String>,
+    pub comment: Option<String>,
+    // alternatives to this move, each starting from the position before it
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+impl PgnMove {
+    pub fn new(piece_move: PieceMove) -> PgnMove {
+        PgnMove {
+            piece_move,
+            nags: Vec::new(),
+            comment_before: None,
+            comment: None,
+            variations: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::parseerror::ParseError;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+// ordered by value, so comparing pieces finds the cheaper attacker
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum Piece {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
+const PIECE_CHARS: [char; 6] = ['P', 'N', 'B', 'R', 'Q', 'K'];
+
+impl Piece {
+    pub const ALL: [Piece; 6] = [
+        Piece::Pawn,
+        Piece::Knight,
+        Piece::Bishop,
+        Piece::Rook,
+        Piece::Queen,
+        Piece::King,
+    ];
+    pub fn index(self) -> usize {
+        self as usize
+    }
+    // uppercase letter as used in SAN, FEN writes black pieces in lowercase
+    pub fn to_char(self) -> char {
+        PIECE_CHARS[self.index()]
+    }
+    // either case
+    pub fn from_char(piece_char: char) -> Option<Piece> {
+        PIECE_CHARS
+            .iter()
+            .position(|upper| *upper == piece_char.to_ascii_uppercase())
+            .map(|piece| Piece::ALL[piece])
+    }
+}
+
+impl From<Piece> for u8 {
+    fn from(piece: Piece) -> u8 {
+        piece as u8
+    }
+}
+
+impl TryFrom<u8> for Piece {
+    type Error = ParseError;
+    fn try_from(index: u8) -> Result<Piece, ParseError> {
+        Piece::ALL
+            .get(index as usize)
+            .copied()
+            .ok_or_else(|| ParseError::BadPiece(index.to_string()))
+    }
+}
+
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl FromStr for Piece {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Piece, ParseError> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(piece_char), None) => {
+                Piece::from_char(piece_char).ok_or_else(|| ParseError::BadPiece(s.to_owned()))
+            }
+            _ => Err(ParseError::BadPiece(s.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::piece::Piece;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_piece() {
+        assert_eq!("N".parse(), Ok(Piece::Knight));
+        assert_eq!("q".parse(), Ok(Piece::Queen));
+        assert!("x".parse::<Piece>().is_err());
+        assert!("nn".parse::<Piece>().is_err());
+        assert_eq!(Piece::Rook.to_string(), "R");
+        assert_eq!(Piece::try_from(5), Ok(Piece::King));
+        assert!(Piece::try_from(6).is_err());
+        assert!(Piece::Pawn < Piece::Queen);
+    }
+}
@@ -1,17 +1,18 @@
 use crate::specialmove::SpecialMove;
+use crate::square::Square;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct PieceMove {
-    pub start: u8,
-    pub end: u8,
+    pub start: Square,
+    pub end: Square,
     pub special: SpecialMove,
 }
 
 impl Default for PieceMove {
     fn default() -> Self {
         PieceMove {
-            start: 0,
-            end: 0,
+            start: Square::A1,
+            end: Square::A1,
             special: SpecialMove::None,
         }
     }
@@ -1,5 +1,12 @@
+use crate::piece::Piece;
+use crate::square::Square;
+
 pub struct PrevGameState {
-    pub capture_piece: u8,
+    pub capture_piece: Piece,
     pub is_capture: bool,
     pub castle_available: [bool; 4],
+    pub en_passant: Option<Square>,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+    pub hash: u64,
 }
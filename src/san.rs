@@ -0,0 +1,244 @@
+use crate::game::Game;
+use crate::moveerror::MoveError;
+use crate::piece::Piece;
+use crate::piecemove::PieceMove;
+use crate::specialmove::SpecialMove;
+use crate::square::Square;
+
+impl Game {
+    // piece_move has to be legal for the side to move
+    pub fn move_to_san(&mut self, piece_move: &PieceMove) -> String {
+        let mut san = match piece_move.special {
+            SpecialMove::CastleKingside => "O-O".to_owned(),
+            SpecialMove::CastleQueenside => "O-O-O".to_owned(),
+            _ => self.move_to_san_body(piece_move),
+        };
+        let prev_game_state = self.play_move(piece_move);
+        if self.in_checkmate(self.side_to_move) {
+            san.push('#');
+        } else if self.in_check(self.side_to_move) {
+            san.push('+');
+        }
+        self.undo_move(piece_move, &prev_game_state);
+        san
+    }
+    fn move_to_san_body(&mut self, piece_move: &PieceMove) -> String {
+        let piece = self.square_to_piece[piece_move.start.index()];
+        let is_capture = self.square_exists[piece_move.end.index()]
+            || piece_move.special == SpecialMove::EnPassant;
+        let mut san = String::new();
+        if piece == Piece::Pawn {
+            if is_capture {
+                san.push((b'a' + piece_move.start.file()) as char);
+            }
+        } else {
+            san.push(piece.to_char());
+            // only name as much of the start square as it takes to tell the moves apart
+            let others: Vec<Square> = self
+                .legal_moves()
+                .into_iter()
+                .filter(|other| {
+                    other.end == piece_move.end
+                        && other.start != piece_move.start
+                        && self.square_to_piece[other.start.index()] == piece
+                })
+                .map(|other| other.start)
+                .collect();
+            if !others.is_empty() {
+                let start = piece_move.start.to_string();
+                if others
+                    .iter()
+                    .all(|other| other.file() != piece_move.start.file())
+                {
+                    san.push_str(&start[..1]);
+                } else if others
+                    .iter()
+                    .all(|other| other.rank() != piece_move.start.rank())
+                {
+                    san.push_str(&start[1..]);
+                } else {
+                    san.push_str(&start);
+                }
+            }
+        }
+        if is_capture {
+            san.push('x');
+        }
+        san.push_str(&piece_move.end.to_string());
+        if let Some(promotion) = piece_move.special.promotion_piece() {
+            san.push('=');
+            san.push(promotion.to_char());
+        }
+        san
+    }
+    // also takes what people tend to write instead: 0-0, missing or extra check marks,
+    // annotation glyphs like ! and ?, and promotions without the =
+    pub fn parse_san_move(&mut self, san: &str) -> Result<PieceMove, MoveError> {
+        let bad_format = || MoveError::BadFormat(san.to_owned());
+        let trimmed = san.trim_end_matches(|c| "+#!?".contains(c));
+        let legal_moves = self.legal_moves();
+        let castle = match trimmed {
+            "O-O" | "0-0" => Some(SpecialMove::CastleKingside),
+            "O-O-O" | "0-0-0" => Some(SpecialMove::CastleQueenside),
+            _ => None,
+        };
+        if let Some(castle) = castle {
+            return legal_moves
+                .into_iter()
+                .find(|piece_move| piece_move.special == castle)
+                .ok_or_else(|| MoveError::Illegal(san.to_owned()));
+        }
+        if !trimmed.is_ascii() {
+            return Err(bad_format());
+        }
+
+        let mut body = trimmed;
+        let piece = match body.chars().next() {
+            Some(piece_char @ ('N' | 'B' | 'R' | 'Q' | 'K')) => {
+                body = &body[1..];
+                Piece::from_char(piece_char).ok_or_else(bad_format)?
+            }
+            _ => Piece::Pawn,
+        };
+        let mut promotion = None;
+        if let Some(promotion_char @ ('N' | 'B' | 'R' | 'Q')) = body.chars().last() {
+            if piece != Piece::Pawn {
+                return Err(bad_format());
+            }
+            promotion = Piece::from_char(promotion_char);
+            body = body[..body.len() - 1].trim_end_matches('=');
+        }
+        if body.len() < 2 {
+            return Err(bad_format());
+        }
+        let end: Square = body[body.len() - 2..].parse().map_err(|_| bad_format())?;
+        // whatever is left names the start file and/or rank, with an optional capture mark
+        let mut start_file = None;
+        let mut start_rank = None;
+        let mut is_capture = false;
+        for disambiguation in body[..body.len() - 2].chars() {
+            match disambiguation {
+                'a'..='h' if start_file.is_none() && start_rank.is_none() && !is_capture => {
+                    start_file = Some(disambiguation as u8 - b'a')
+                }
+                '1'..='8' if start_rank.is_none() && !is_capture => {
+                    start_rank = Some(disambiguation as u8 - b'1')
+                }
+                'x' if !is_capture => is_capture = true,
+                _ => return Err(bad_format()),
+            }
+        }
+
+        let mut matching = legal_moves.into_iter().filter(|piece_move| {
+            piece_move.end == end
+                && self.square_to_piece[piece_move.start.index()] == piece
+                && piece_move.special.promotion_piece() == promotion
+                && start_file.is_none_or(|file| piece_move.start.file() == file)
+                && start_rank.is_none_or(|rank| piece_move.start.rank() == rank)
+        });
+        match (matching.next(), matching.next()) {
+            (Some(piece_move), None) => Ok(piece_move),
+            (Some(_), Some(_)) => Err(MoveError::Ambiguous(san.to_owned())),
+            (None, _) => Err(MoveError::Illegal(san.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::Game;
+    use crate::moveerror::MoveError;
+
+    fn san_of(game: &mut Game, uci_move: &str) -> String {
+        let piece_move = game.parse_uci_move(uci_move).unwrap();
+        game.move_to_san(&piece_move)
+    }
+
+    #[test]
+    fn test_move_to_san() {
+        let mut game = Game::new();
+        game.starting_game();
+        assert_eq!(san_of(&mut game, "e2e4"), "e4");
+        assert_eq!(san_of(&mut game, "g1f3"), "Nf3");
+
+        let mut game =
+            Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        assert_eq!(san_of(&mut game, "e1g1"), "O-O");
+        assert_eq!(san_of(&mut game, "e1c1"), "O-O-O");
+        assert_eq!(san_of(&mut game, "e2a6"), "Bxa6");
+        assert_eq!(san_of(&mut game, "d5e6"), "dxe6");
+        assert_eq!(san_of(&mut game, "e5f7"), "Nxf7");
+        assert_eq!(san_of(&mut game, "f3f6"), "Qxf6");
+        // both knights and both rooks can reach the same squares
+        let mut game = Game::from_fen("4k3/8/8/8/8/2N3N1/5K2/R6R w - - 0 1").unwrap();
+        assert_eq!(san_of(&mut game, "g3e2"), "Nge2");
+        assert_eq!(san_of(&mut game, "c3e2"), "Nce2");
+        assert_eq!(san_of(&mut game, "a1d1"), "Rad1");
+        let mut game = Game::from_fen("4k3/8/8/8/R7/8/8/R3K3 w - - 0 1").unwrap();
+        assert_eq!(san_of(&mut game, "a1a2"), "R1a2");
+        let mut game = Game::from_fen("4k3/8/8/8/8/Q1Q5/8/Q3K3 w - - 0 1").unwrap();
+        assert_eq!(san_of(&mut game, "a3b2"), "Qa3b2");
+
+        let mut game = Game::from_fen("8/2P5/8/8/8/8/8/k1K5 w - - 0 1").unwrap();
+        assert_eq!(san_of(&mut game, "c7c8q"), "c8=Q");
+        assert_eq!(san_of(&mut game, "c7c8n"), "c8=N");
+        let mut game = Game::from_fen("3r2k1/8/8/8/8/8/8/1K5R b - - 0 1").unwrap();
+        assert_eq!(san_of(&mut game, "d8d1"), "Rd1+");
+        let mut game = Game::from_fen("6k1/5ppp/8/8/8/8/8/1K2R3 w - - 0 1").unwrap();
+        assert_eq!(san_of(&mut game, "e1e8"), "Re8#");
+        let mut game =
+            Game::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+                .unwrap();
+        assert_eq!(san_of(&mut game, "e5f6"), "exf6");
+    }
+
+    #[test]
+    fn test_parse_san_move() {
+        let mut game =
+            Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        for piece_move in game.legal_moves() {
+            let san = game.move_to_san(&piece_move);
+            assert_eq!(game.parse_san_move(&san), Ok(piece_move), "{}", san);
+        }
+        let castle = game.parse_uci_move("e1g1").unwrap();
+        assert_eq!(game.parse_san_move("0-0"), Ok(castle));
+        let capture = game.parse_uci_move("e2a6").unwrap();
+        assert_eq!(game.parse_san_move("Bxa6!?"), Ok(capture));
+        assert_eq!(game.parse_san_move("Ba6"), Ok(capture));
+        assert_eq!(game.parse_san_move("Be2a6"), Ok(capture));
+        assert_eq!(
+            game.parse_san_move("Nd2"),
+            Err(MoveError::Illegal("Nd2".to_owned()))
+        );
+        assert_eq!(
+            game.parse_san_move("Rb2"),
+            Err(MoveError::Illegal("Rb2".to_owned()))
+        );
+
+        let mut game = Game::from_fen("4k3/8/8/8/8/2N3N1/5K2/R6R w - - 0 1").unwrap();
+        assert_eq!(
+            game.parse_san_move("Ne2"),
+            Err(MoveError::Ambiguous("Ne2".to_owned()))
+        );
+        assert_eq!(
+            game.parse_san_move("Nge2"),
+            Ok(game.parse_uci_move("g3e2").unwrap())
+        );
+
+        let mut game = Game::from_fen("8/2P5/8/8/8/8/8/k1K5 w - - 0 1").unwrap();
+        let promotion = game.parse_uci_move("c7c8q").unwrap();
+        assert_eq!(game.parse_san_move("c8=Q"), Ok(promotion));
+        assert_eq!(game.parse_san_move("c8Q+"), Ok(promotion));
+        assert!(game.parse_san_move("c8").is_err());
+        for san in &["", "O-O-O-O", "Zf3", "Nf9", "e", "Kxxd2", "Ng1=Q", "e4é"] {
+            assert_eq!(
+                game.parse_san_move(san),
+                Err(MoveError::BadFormat(san.to_string())),
+                "{}",
+                san
+            );
+        }
+    }
+}
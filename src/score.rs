@@ -0,0 +1,121 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+// Centipawns from the side to move's point of view. A mate found n plies from the root scores
+// MATE - n, so nearer mates are worth more, and getting mated scores the negation.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Score(pub i32);
+
+// longest mate that still counts as one, far past any depth the search can reach
+const MAX_MATE_PLY: i32 = 1000;
+
+impl Score {
+    pub const DRAW: Score = Score(0);
+    pub const MATE: Score = Score(30000);
+    // beyond every real score, for the initial search window
+    pub const INFINITY: Score = Score(32000);
+
+    // mating ply plies from the root
+    pub fn mate_in(ply: usize) -> Score {
+        Score(Score::MATE.0 - ply as i32)
+    }
+    pub fn is_mate(self) -> bool {
+        self.0.abs() > Score::MATE.0 - MAX_MATE_PLY
+    }
+    // moves to mate as UCI counts them, negative when getting mated
+    pub fn mate_moves(self) -> Option<i32> {
+        if !self.is_mate() {
+            return None;
+        }
+        let plies = Score::MATE.0 - self.0.abs();
+        Some(if self.0 > 0 {
+            (plies + 1) / 2
+        } else {
+            -plies / 2
+        })
+    }
+    pub fn abs(self) -> Score {
+        Score(self.0.abs())
+    }
+    // The table is shared by nodes at every ply, so it stores mates counted from the node
+    // instead of from the root.
+    pub fn to_tt(self, ply: usize) -> Score {
+        if !self.is_mate() {
+            return self;
+        }
+        Score(self.0 + self.0.signum() * ply as i32)
+    }
+    pub fn from_tt(score: Score, ply: usize) -> Score {
+        if !score.is_mate() {
+            return score;
+        }
+        Score(score.0 - score.0.signum() * ply as i32)
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+    fn add(self, other: Score) -> Score {
+        Score(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, other: Score) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+    fn sub(self, other: Score) -> Score {
+        Score(self.0 - other.0)
+    }
+}
+
+impl SubAssign for Score {
+    fn sub_assign(&mut self, other: Score) {
+        self.0 -= other.0;
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+    fn neg(self) -> Score {
+        Score(-self.0)
+    }
+}
+
+// the score part of a UCI info line, cp or mate
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mate_moves() {
+            Some(moves) => write!(f, "mate {}", moves),
+            None => write!(f, "cp {}", self.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::score::Score;
+
+    #[test]
+    fn test_score() {
+        assert!(Score::mate_in(1) > Score::mate_in(9));
+        assert!(-Score::mate_in(2) < -Score::mate_in(8));
+        assert!(Score::mate_in(9) > Score(9000));
+        assert_eq!(Score::mate_in(1).mate_moves(), Some(1));
+        assert_eq!(Score::mate_in(3).mate_moves(), Some(2));
+        assert_eq!((-Score::mate_in(4)).mate_moves(), Some(-2));
+        assert_eq!(Score(-25).mate_moves(), None);
+        assert_eq!(Score(-25).to_string(), "cp -25");
+        assert_eq!(Score::mate_in(5).to_string(), "mate 3");
+        assert_eq!((-Score::mate_in(2)).to_string(), "mate -1");
+        // a mate 5 plies from the root is 3 plies from a node at ply 2, and back again
+        assert_eq!(Score::mate_in(5).to_tt(2), Score::mate_in(3));
+        assert_eq!(Score::from_tt(Score::mate_in(3), 2), Score::mate_in(5));
+        assert_eq!(Score::from_tt(-Score::mate_in(3), 2), -Score::mate_in(5));
+        assert_eq!(Score(150).to_tt(4), Score(150));
+    }
+}
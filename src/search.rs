@@ -0,0 +1,508 @@
+use crate::bound::Bound;
+use crate::engineoptions::EngineOptions;
+use crate::game::Game;
+use crate::piecemove::PieceMove;
+use crate::score::Score;
+use crate::searchcontrol::SearchControl;
+use crate::searchinfo::SearchInfo;
+use crate::searchlimits::SearchLimits;
+use crate::searchresult::SearchResult;
+use crate::transpositiontable::TranspositionTable;
+use crate::ttentry::TtEntry;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+// how far either side of the last iteration's score the first window of the next one reaches
+const ASPIRATION_DELTA: Score = Score(50);
+
+// Iterative deepening under the limits of a go command. Returns the result of the deepest
+// finished iteration, or None if there is nothing to play.
+pub fn search(
+    game: &mut Game,
+    limits: &SearchLimits,
+    options: &EngineOptions,
+    tt: &Arc<TranspositionTable>,
+) -> Option<SearchResult> {
+    let control = Arc::new(SearchControl::from_limits(
+        limits,
+        options,
+        game.side_to_move,
+    ));
+    search_with_control(game, limits, &control, tt)
+}
+
+// search, but stop and ponderhit can come from other threads through control
+pub fn search_with_control(
+    game: &mut Game,
+    limits: &SearchLimits,
+    control: &Arc<SearchControl>,
+    tt: &Arc<TranspositionTable>,
+) -> Option<SearchResult> {
+    control.set_root_ply(game.hash_history.len());
+    let mut root_moves = game.legal_moves();
+    if !limits.search_moves.is_empty() {
+        root_moves.retain(|piece_move| limits.search_moves.contains(piece_move));
+    }
+    // something legal to play even if the first iteration runs out of budget
+    let first = *root_moves.first()?;
+    let mut found = SearchResult {
+        best_move: first,
+        score: crate::eval(game, 0, &control.options().piece_scores),
+        pv: vec![first],
+    };
+    let line_count = control.options().multi_pv.min(root_moves.len());
+    for depth in 1..=limits.max_depth() {
+        if control.should_stop() {
+            break;
+        }
+        // each line after the first is the best move left once the earlier ones are taken out
+        let mut lines = Vec::new();
+        let mut line_moves = root_moves.clone();
+        while lines.len() < line_count {
+            // the other lines need exact scores, so only the best one gets a narrow window
+            let line = if lines.is_empty() && depth > 1 {
+                aspiration_search(game, depth, &line_moves, found.score, control, tt)
+            } else {
+                let (alpha, beta) = (-Score::INFINITY, Score::INFINITY);
+                crate::best_move(game, depth, &line_moves, alpha, beta, control, tt)
+            };
+            match line {
+                Some(line) => {
+                    line_moves.retain(|piece_move| *piece_move != line.best_move);
+                    lines.push(line);
+                }
+                None => break,
+            }
+        }
+        match lines.first() {
+            Some(best_move) => found = best_move.clone(),
+            None => break,
+        }
+        // the later lines stored their own root entries, so put the best one back for the next
+        // iteration's ordering and window
+        if lines.len() > 1 {
+            tt.store(
+                game.hash,
+                TtEntry {
+                    depth,
+                    bound: Bound::Exact,
+                    score: found.score,
+                    best_move: Some(found.best_move),
+                },
+            );
+        }
+        for (line_idx, line) in lines.iter().enumerate() {
+            let multi_pv = if line_count > 1 {
+                Some(line_idx + 1)
+            } else {
+                None
+            };
+            control.report(iteration_info(depth, line, multi_pv, control));
+        }
+        if lines.len() < line_count {
+            break;
+        }
+        // deepening finds the shortest mate first, so the first one found is the answer
+        if limits.mate.is_some() && mates(found.score) {
+            break;
+        }
+    }
+    // UCI doesn't allow a bestmove before stop in infinite mode, or before ponderhit
+    while (limits.infinite || control.pondering()) && !control.stopped() {
+        thread::sleep(Duration::from_millis(1));
+    }
+    Some(found)
+}
+
+// Searches a window around the score the last iteration expects. It cuts off more than a full
+// window, and whichever side the score falls out of gets widened until it lands inside.
+fn aspiration_search(
+    game: &mut Game,
+    depth: u8,
+    root_moves: &[PieceMove],
+    expected: Score,
+    control: &Arc<SearchControl>,
+    tt: &Arc<TranspositionTable>,
+) -> Option<SearchResult> {
+    let mut delta = ASPIRATION_DELTA;
+    // mate scores jump by more than any delta, so don't bother
+    let (mut alpha, mut beta) = if expected.is_mate() {
+        (-Score::INFINITY, Score::INFINITY)
+    } else {
+        (expected - delta, expected + delta)
+    };
+    loop {
+        let result = crate::best_move(game, depth, root_moves, alpha, beta, control, tt)?;
+        if result.score <= alpha && alpha > -Score::INFINITY {
+            alpha = (alpha - delta).max(-Score::INFINITY);
+        } else if result.score >= beta && beta < Score::INFINITY {
+            beta = (beta + delta).min(Score::INFINITY);
+        } else {
+            return Some(result);
+        }
+        delta += delta;
+    }
+}
+
+fn iteration_info(
+    depth: u8,
+    line: &SearchResult,
+    multi_pv: Option<usize>,
+    control: &SearchControl,
+) -> SearchInfo {
+    SearchInfo::Iteration {
+        depth,
+        seldepth: control.seldepth(),
+        multi_pv,
+        score: line.score,
+        nodes: control.nodes(),
+        time: control.elapsed(),
+        pv: line.pv.clone(),
+    }
+}
+
+fn mates(score: Score) -> bool {
+    score.mate_moves().is_some_and(|moves| moves > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engineoptions::EngineOptions;
+    use crate::game::Game;
+    use crate::score::Score;
+    use crate::searchcontrol::SearchControl;
+    use crate::searchhandle::SearchHandle;
+    use crate::searchinfo::SearchInfo;
+    use crate::searchlimits::SearchLimits;
+    use crate::searchresult::SearchResult;
+    use crate::transpositiontable::TranspositionTable;
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_search_limits() {
+        let options = EngineOptions::default();
+        let tt = Arc::new(TranspositionTable::new(1));
+        let mut game = Game::from_fen("6k1/5ppp/8/8/8/8/8/1K2R3 w - - 0 1").unwrap();
+        let mate = game.parse_uci_move("e1e8").unwrap();
+        let limits = SearchLimits {
+            mate: Some(1),
+            ..SearchLimits::default()
+        };
+        assert_eq!(
+            crate::search::search(&mut game, &limits, &options, &tt),
+            Some(SearchResult {
+                best_move: mate,
+                score: Score::mate_in(1),
+                pv: vec![mate],
+            })
+        );
+
+        // searchmoves keeps the engine away from the mate
+        let king_move = game.parse_uci_move("b1a1").unwrap();
+        let limits = SearchLimits {
+            depth: Some(2),
+            search_moves: vec![king_move],
+            ..SearchLimits::default()
+        };
+        assert_eq!(
+            crate::search::search(&mut game, &limits, &options, &tt).map(|result| result.best_move),
+            Some(king_move)
+        );
+
+        // a node limit too small for one iteration still gives a legal move
+        let limits = SearchLimits {
+            nodes: Some(1),
+            ..SearchLimits::default()
+        };
+        let piece_move = crate::search::search(&mut game, &limits, &options, &tt)
+            .unwrap()
+            .best_move;
+        assert!(game.legal_moves().contains(&piece_move));
+
+        let mut game = Game::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(
+            crate::search::search(&mut game, &limits, &options, &tt),
+            None
+        );
+    }
+
+    #[test]
+    fn test_search_handle() {
+        let options = EngineOptions::default();
+        let tt = Arc::new(TranspositionTable::new(1));
+        let mut game = Game::new();
+        game.starting_game();
+        let (tx, rx) = mpsc::channel();
+        let limits = SearchLimits {
+            infinite: true,
+            ..SearchLimits::default()
+        };
+        let search = SearchHandle::spawn(
+            game.clone(),
+            limits,
+            &options,
+            Arc::clone(&tt),
+            |_| (),
+            move |result| tx.send(result).unwrap(),
+        );
+        thread::sleep(Duration::from_millis(50));
+        assert!(!search.is_finished());
+        search.stop();
+        let piece_move = rx
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap()
+            .unwrap()
+            .best_move;
+        assert!(game.legal_moves().contains(&piece_move));
+        search.join();
+
+        // pondering ignores the clock until ponderhit
+        let (tx, rx) = mpsc::channel();
+        let limits = SearchLimits {
+            move_time: Some(1),
+            ponder: true,
+            ..SearchLimits::default()
+        };
+        let search = SearchHandle::spawn(
+            game.clone(),
+            limits,
+            &options,
+            tt,
+            |_| (),
+            move |result| tx.send(result).unwrap(),
+        );
+        thread::sleep(Duration::from_millis(50));
+        assert!(!search.is_finished());
+        search.ponderhit();
+        assert!(rx.recv_timeout(Duration::from_secs(5)).unwrap().is_some());
+        search.join();
+    }
+
+    #[test]
+    fn test_search_info() {
+        let options = EngineOptions::default();
+        let tt = Arc::new(TranspositionTable::new(1));
+        let mut game = Game::from_fen("6k1/5ppp/8/8/8/8/8/1K2R3 w - - 0 1").unwrap();
+        let limits = SearchLimits {
+            depth: Some(3),
+            ..SearchLimits::default()
+        };
+        let infos = Arc::new(Mutex::new(Vec::new()));
+        let thread_infos = Arc::clone(&infos);
+        let control = Arc::new(
+            SearchControl::from_limits(&limits, &options, game.side_to_move)
+                .with_info(move |info| thread_infos.lock().unwrap().push(info)),
+        );
+        crate::search::search_with_control(&mut game, &limits, &control, &tt);
+        let infos = infos.lock().unwrap();
+        assert_eq!(infos.len(), 3);
+        let mut nodes = 0;
+        for (info, expected_depth) in infos.iter().zip(1..) {
+            match info {
+                SearchInfo::Iteration {
+                    depth,
+                    score,
+                    nodes: iteration_nodes,
+                    pv,
+                    ..
+                } => {
+                    assert_eq!(*depth, expected_depth);
+                    assert_eq!(*score, Score::mate_in(1));
+                    assert_eq!(pv, &vec![game.parse_uci_move("e1e8").unwrap()]);
+                    assert!(*iteration_nodes >= nodes);
+                    nodes = *iteration_nodes;
+                }
+                _ => panic!("unexpected {:?}", info),
+            }
+        }
+        assert!(infos[2].to_string().starts_with("info depth 3 seldepth "));
+        assert!(infos[2].to_string().contains(" score mate 1 nodes "));
+        assert!(infos[2].to_string().ends_with(" pv e1e8"));
+
+        let info = SearchInfo::Iteration {
+            depth: 2,
+            seldepth: 5,
+            multi_pv: Some(2),
+            score: Score(-25),
+            nodes: 3000,
+            time: 1500,
+            pv: vec![
+                game.parse_uci_move("b1a1").unwrap(),
+                game.parse_uci_move("b1a2").unwrap(),
+            ],
+        };
+        assert_eq!(
+            info.to_string(),
+            "info depth 2 seldepth 5 multipv 2 score cp -25 nodes 3000 nps 2000 time 1500 pv b1a1 b1a2"
+        );
+        let info = SearchInfo::CurrMove {
+            depth: 7,
+            piece_move: game.parse_uci_move("b1a1").unwrap(),
+            number: 3,
+        };
+        assert_eq!(
+            info.to_string(),
+            "info depth 7 currmove b1a1 currmovenumber 3"
+        );
+    }
+
+    #[test]
+    fn test_pv() {
+        let options = EngineOptions {
+            threads: 1,
+            ..EngineOptions::default()
+        };
+        let tt = Arc::new(TranspositionTable::new(1));
+        let mut game = Game::new();
+        game.starting_game();
+        let limits = SearchLimits {
+            depth: Some(4),
+            ..SearchLimits::default()
+        };
+        let result = crate::search::search(&mut game, &limits, &options, &tt).unwrap();
+        assert_eq!(result.pv.len(), 4);
+        assert_eq!(result.pv[0], result.best_move);
+        // the whole line has to be playable from the root
+        for piece_move in &result.pv {
+            assert!(game.legal_moves().contains(piece_move));
+            game.play_move(piece_move);
+        }
+    }
+
+    #[test]
+    fn test_aspiration_windows() {
+        let options = EngineOptions {
+            threads: 1,
+            ..EngineOptions::default()
+        };
+        let control = Arc::new(SearchControl::new(None, None, options));
+        let mut game = Game::from_fen("7k/8/8/1p1r4/8/2N5/8/K2Q4 w - - 0 1").unwrap();
+        let root_moves = game.legal_moves();
+        let full_window = crate::best_move(
+            &mut game,
+            3,
+            &root_moves,
+            -Score::INFINITY,
+            Score::INFINITY,
+            &control,
+            &Arc::new(TranspositionTable::new(1)),
+        )
+        .unwrap();
+        // a window far off the real score fails until it has widened enough to agree
+        for expected in [full_window.score, Score(900), Score(-900)] {
+            let tt = Arc::new(TranspositionTable::new(1));
+            let aspirated =
+                super::aspiration_search(&mut game, 3, &root_moves, expected, &control, &tt)
+                    .unwrap();
+            assert_eq!(aspirated.score, full_window.score);
+        }
+    }
+
+    #[test]
+    fn test_multi_pv() {
+        let options = EngineOptions {
+            threads: 1,
+            multi_pv: 2,
+            ..EngineOptions::default()
+        };
+        let tt = Arc::new(TranspositionTable::new(1));
+        let mut game = Game::from_fen("6k1/5ppp/8/8/8/8/8/1K2R3 w - - 0 1").unwrap();
+        let limits = SearchLimits {
+            depth: Some(2),
+            ..SearchLimits::default()
+        };
+        let infos = Arc::new(Mutex::new(Vec::new()));
+        let thread_infos = Arc::clone(&infos);
+        let control = Arc::new(
+            SearchControl::from_limits(&limits, &options, game.side_to_move)
+                .with_info(move |info| thread_infos.lock().unwrap().push(info.to_string())),
+        );
+        let mate = game.parse_uci_move("e1e8").unwrap();
+        assert_eq!(
+            crate::search::search_with_control(&mut game, &limits, &control, &tt),
+            Some(SearchResult {
+                best_move: mate,
+                score: Score::mate_in(1),
+                pv: vec![mate],
+            })
+        );
+        let infos = infos.lock().unwrap();
+        assert_eq!(infos.len(), 4);
+        assert!(infos[2].starts_with("info depth 2 seldepth 2 multipv 1 score mate 1 "));
+        assert!(infos[3].starts_with("info depth 2 seldepth 2 multipv 2 score cp "));
+        assert!(!infos[3].contains("pv e1e8"));
+        // the table keeps the best line for the root, not the last one searched
+        assert_eq!(tt.probe(game.hash).unwrap().best_move, Some(mate));
+    }
+
+    #[test]
+    fn test_max_piece_values() {
+        let mut options = EngineOptions {
+            threads: 1,
+            ..EngineOptions::default()
+        };
+        for name in ["Pawn", "Knight", "Bishop", "Rook", "Queen"] {
+            options.set(&format!("{} Value", name), "1500").unwrap();
+        }
+        assert!(options.set("Queen Value", "1501").is_err());
+        // all the material on one side is still only material, not a mate
+        let tt = Arc::new(TranspositionTable::new(1));
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/PPPPPPPP/RNBQKBNR w KQ - 0 1").unwrap();
+        let limits = SearchLimits {
+            depth: Some(2),
+            ..SearchLimits::default()
+        };
+        let result = crate::search::search(&mut game, &limits, &options, &tt).unwrap();
+        assert!(result.score > Score(20000));
+        assert!(!result.score.is_mate());
+    }
+
+    #[test]
+    fn test_time_budget() {
+        use crate::color::Color;
+        let limits = SearchLimits {
+            times: [Some(70000), Some(1000)],
+            increments: [0, 2000],
+            ..SearchLimits::default()
+        };
+        assert_eq!(limits.time_budget(Color::White, 0), Some(2000));
+        assert_eq!(limits.time_budget(Color::Black, 0), Some(900));
+        let limits = SearchLimits {
+            moves_to_go: Some(10),
+            ..limits
+        };
+        assert_eq!(limits.time_budget(Color::White, 0), Some(7000));
+        let limits = SearchLimits {
+            move_time: Some(500),
+            ..limits
+        };
+        assert_eq!(limits.time_budget(Color::White, 0), Some(500));
+        assert_eq!(limits.time_budget(Color::White, 50), Some(450));
+        let limits = SearchLimits {
+            infinite: true,
+            ..limits
+        };
+        assert_eq!(limits.time_budget(Color::White, 0), None);
+        assert_eq!(limits.max_depth(), u8::MAX);
+        let limits = SearchLimits {
+            depth: Some(9),
+            mate: Some(3),
+            ..SearchLimits::default()
+        };
+        assert_eq!(limits.max_depth(), 5);
+        assert_eq!(limits.time_budget(Color::White, 0), None);
+        // nothing to stop the search but a short default, whoever's clock was given
+        assert_eq!(
+            SearchLimits::default().time_budget(Color::White, 0),
+            Some(1000)
+        );
+        let limits = SearchLimits {
+            times: [Some(70000), None],
+            ..SearchLimits::default()
+        };
+        assert_eq!(limits.time_budget(Color::Black, 100), Some(900));
+    }
+}
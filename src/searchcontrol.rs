@@ -0,0 +1,126 @@
+use crate::color::Color;
+use crate::engineoptions::EngineOptions;
+use crate::searchinfo::SearchInfo;
+use crate::searchlimits::SearchLimits;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::Instant;
+
+// Budget for one search, shared by every search thread and every iteration of the deepening
+// loop. Searches give up (return None) as soon as should_stop says so.
+pub struct SearchControl {
+    start_time: Instant,
+    time_budget: Option<i128>,
+    // ms after start_time that the time budget counts from, moved up by ponderhit
+    budget_start: AtomicU64,
+    node_limit: Option<u64>,
+    nodes: AtomicU64,
+    // game plies (hash_history lengths) of the root and of the deepest node searched
+    root_ply: AtomicUsize,
+    max_ply: AtomicUsize,
+    pondering: AtomicBool,
+    stopped: AtomicBool,
+    on_info: Option<Box<dyn Fn(SearchInfo) + Send + Sync>>,
+    options: EngineOptions,
+}
+
+impl SearchControl {
+    pub fn new(
+        time_budget: Option<i128>,
+        node_limit: Option<u64>,
+        options: EngineOptions,
+    ) -> SearchControl {
+        SearchControl {
+            start_time: Instant::now(),
+            time_budget,
+            budget_start: AtomicU64::new(0),
+            node_limit,
+            nodes: AtomicU64::new(0),
+            root_ply: AtomicUsize::new(0),
+            max_ply: AtomicUsize::new(0),
+            pondering: AtomicBool::new(false),
+            stopped: AtomicBool::new(false),
+            on_info: None,
+            options,
+        }
+    }
+    pub fn unlimited() -> SearchControl {
+        SearchControl::new(None, None, EngineOptions::default())
+    }
+    pub fn from_limits(
+        limits: &SearchLimits,
+        options: &EngineOptions,
+        color: Color,
+    ) -> SearchControl {
+        let node_limit = if limits.infinite { None } else { limits.nodes };
+        let time_budget = limits.time_budget(color, options.move_overhead);
+        let control = SearchControl::new(time_budget, node_limit, options.clone());
+        control.pondering.store(limits.ponder, Ordering::Relaxed);
+        control
+    }
+    pub fn with_info<F>(mut self, on_info: F) -> SearchControl
+    where
+        F: Fn(SearchInfo) + Send + Sync + 'static,
+    {
+        self.on_info = Some(Box::new(on_info));
+        self
+    }
+    pub fn report(&self, info: SearchInfo) {
+        if let Some(on_info) = &self.on_info {
+            on_info(info);
+        }
+    }
+    pub fn set_root_ply(&self, ply: usize) {
+        self.root_ply.store(ply, Ordering::Relaxed);
+        self.max_ply.store(ply, Ordering::Relaxed);
+    }
+    pub fn options(&self) -> &EngineOptions {
+        &self.options
+    }
+    pub fn count_node(&self, ply: usize) {
+        self.nodes.fetch_add(1, Ordering::Relaxed);
+        self.max_ply.fetch_max(ply, Ordering::Relaxed);
+    }
+    // plies between the root and a node with this many hash_history entries
+    pub fn ply(&self, history_len: usize) -> usize {
+        history_len.saturating_sub(self.root_ply.load(Ordering::Relaxed))
+    }
+    pub fn nodes(&self) -> u64 {
+        self.nodes.load(Ordering::Relaxed)
+    }
+    pub fn seldepth(&self) -> usize {
+        self.max_ply
+            .load(Ordering::Relaxed)
+            .saturating_sub(self.root_ply.load(Ordering::Relaxed))
+    }
+    pub fn elapsed(&self) -> i128 {
+        self.start_time.elapsed().as_millis() as i128
+    }
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Release);
+    }
+    pub fn stopped(&self) -> bool {
+        self.stopped.load(Ordering::Acquire)
+    }
+    // the opponent played the move we pondered on, so the clock is ours from now on
+    pub fn ponderhit(&self) {
+        self.budget_start
+            .store(self.elapsed() as u64, Ordering::Relaxed);
+        self.pondering.store(false, Ordering::Release);
+    }
+    pub fn pondering(&self) -> bool {
+        self.pondering.load(Ordering::Acquire)
+    }
+    pub fn should_stop(&self) -> bool {
+        if self.stopped() {
+            return true;
+        }
+        let out_of_time = !self.pondering()
+            && self.time_budget.is_some_and(|time_budget| {
+                self.elapsed() - self.budget_start.load(Ordering::Relaxed) as i128 > time_budget
+            });
+        out_of_time
+            || self
+                .node_limit
+                .is_some_and(|node_limit| self.nodes() >= node_limit)
+    }
+}
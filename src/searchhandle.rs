@@ -0,0 +1,70 @@
+use crate::engineoptions::EngineOptions;
+use crate::game::Game;
+use crate::search;
+use crate::searchcontrol::SearchControl;
+use crate::searchinfo::SearchInfo;
+use crate::searchlimits::SearchLimits;
+use crate::searchresult::SearchResult;
+use crate::transpositiontable::TranspositionTable;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+// A search running on its own thread, which can be stopped or told about a ponderhit while the
+// caller keeps reading commands.
+pub struct SearchHandle {
+    control: Arc<SearchControl>,
+    thread: JoinHandle<()>,
+    infinite: bool,
+}
+
+impl SearchHandle {
+    // Both callbacks run on search threads. on_info gets progress as it happens and on_finish
+    // the result, once the search stops by itself or is stopped.
+    pub fn spawn<I, F>(
+        mut game: Game,
+        limits: SearchLimits,
+        options: &EngineOptions,
+        tt: Arc<TranspositionTable>,
+        on_info: I,
+        on_finish: F,
+    ) -> SearchHandle
+    where
+        I: Fn(SearchInfo) + Send + Sync + 'static,
+        F: FnOnce(Option<SearchResult>) + Send + 'static,
+    {
+        let control = Arc::new(
+            SearchControl::from_limits(&limits, options, game.side_to_move).with_info(on_info),
+        );
+        let infinite = limits.infinite;
+        let thread_control = Arc::clone(&control);
+        let thread = thread::spawn(move || {
+            on_finish(search::search_with_control(
+                &mut game,
+                &limits,
+                &thread_control,
+                &tt,
+            ));
+        });
+        SearchHandle {
+            control,
+            thread,
+            infinite,
+        }
+    }
+    pub fn stop(&self) {
+        self.control.stop();
+    }
+    pub fn ponderhit(&self) {
+        self.control.ponderhit();
+    }
+    // infinite and ponder searches only end with stop (or ponderhit)
+    pub fn waits_for_stop(&self) -> bool {
+        self.infinite || self.control.pondering()
+    }
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+    pub fn join(self) {
+        self.thread.join().expect("Search thread panicked");
+    }
+}
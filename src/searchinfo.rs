@@ -0,0 +1,64 @@
+use crate::piecemove::PieceMove;
+use crate::score::Score;
+use std::fmt;
+
+// progress reports from a running search, Display gives the UCI info line
+#[derive(Clone, Debug, PartialEq)]
+pub enum SearchInfo {
+    Iteration {
+        depth: u8,
+        seldepth: usize,
+        // which line this is when several are searched
+        multi_pv: Option<usize>,
+        // from the side to move's point of view
+        score: Score,
+        nodes: u64,
+        time: i128,
+        pv: Vec<PieceMove>,
+    },
+    CurrMove {
+        depth: u8,
+        piece_move: PieceMove,
+        number: usize,
+    },
+}
+
+impl fmt::Display for SearchInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SearchInfo::Iteration {
+                depth,
+                seldepth,
+                multi_pv,
+                score,
+                nodes,
+                time,
+                pv,
+            } => {
+                write!(f, "info depth {} seldepth {}", depth, seldepth)?;
+                if let Some(multi_pv) = multi_pv {
+                    write!(f, " multipv {}", multi_pv)?;
+                }
+                write!(f, " score {}", score)?;
+                let nps = *nodes as i128 * 1000 / (*time).max(1);
+                write!(f, " nodes {} nps {} time {}", nodes, nps, time)?;
+                if !pv.is_empty() {
+                    write!(f, " pv")?;
+                    for piece_move in pv {
+                        write!(f, " {}", piece_move)?;
+                    }
+                }
+                Ok(())
+            }
+            SearchInfo::CurrMove {
+                depth,
+                piece_move,
+                number,
+            } => write!(
+                f,
+                "info depth {} currmove {} currmovenumber {}",
+                depth, piece_move, number
+            ),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use crate::color::Color;
+use crate::piecemove::PieceMove;
+
+// milliseconds for a go that sets no clock for the side to move and no other limit
+const DEFAULT_MOVE_TIME: i128 = 1000;
+
+// everything a UCI go command can ask for, none set means a short search and infinite means
+// search until stopped
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchLimits {
+    pub depth: Option<u8>,
+    pub nodes: Option<u64>,
+    // find a mate in this many moves
+    pub mate: Option<u8>,
+    pub move_time: Option<i128>,
+    // indexed by color, like piece_positions
+    pub times: [Option<i128>; 2],
+    pub increments: [i128; 2],
+    pub moves_to_go: Option<u32>,
+    pub infinite: bool,
+    // search the expected reply to our move without a clock until ponderhit
+    pub ponder: bool,
+    // only these root moves are searched, all of them if empty
+    pub search_moves: Vec<PieceMove>,
+}
+
+impl SearchLimits {
+    // milliseconds color can spend on this move, after move_overhead for lag
+    pub fn time_budget(&self, color: Color, move_overhead: i128) -> Option<i128> {
+        if self.infinite {
+            return None;
+        }
+        if let Some(move_time) = self.move_time {
+            return Some((move_time - move_overhead).max(1));
+        }
+        let time = match self.times[color.index()] {
+            Some(time) => (time - move_overhead).max(1),
+            // without any limit the search would never send its bestmove
+            None if self.depth.is_none() && self.nodes.is_none() && self.mate.is_none() => {
+                return Some((DEFAULT_MOVE_TIME - move_overhead).max(1));
+            }
+            None => return None,
+        };
+        // spread the clock over the moves left until the next time control
+        let moves_left = self
+            .moves_to_go
+            .map_or(35, |moves_to_go| moves_to_go.clamp(1, 35));
+        let budget = time / moves_left as i128 + self.increments[color.index()];
+        // the increment only arrives after the move, so never bet the whole clock on it
+        Some(budget.min(time - time / 10).max(1))
+    }
+    pub fn max_depth(&self) -> u8 {
+        if self.infinite {
+            return u8::MAX;
+        }
+        let mate_depth = self
+            .mate
+            .map_or(u8::MAX, |mate| mate.saturating_mul(2).saturating_sub(1));
+        self.depth.unwrap_or(u8::MAX).min(mate_depth)
+    }
+}
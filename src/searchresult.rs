@@ -0,0 +1,10 @@
+use crate::piecemove::PieceMove;
+use crate::score::Score;
+
+// What a search settled on. pv is the line it expects from here, starting with best_move.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchResult {
+    pub best_move: PieceMove,
+    pub score: Score,
+    pub pv: Vec<PieceMove>,
+}
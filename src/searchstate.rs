@@ -0,0 +1,68 @@
+use crate::color::Color;
+use crate::piecemove::PieceMove;
+
+// past this, history scores would start to catch up with the killers and captures
+const MAX_HISTORY: i32 = 1 << 20;
+
+// What one search thread learns on the way down, indexed by ply from the root. Every thread has
+// its own, so nothing here is shared.
+#[derive(Clone, Debug, Default)]
+pub struct SearchState {
+    // a triangular pv table, pv_lines[ply] is the best line found so far from the node at ply
+    pv_lines: Vec<Vec<PieceMove>>,
+    // the last two quiet moves that caused a cutoff at each ply
+    killers: Vec<[Option<PieceMove>; 2]>,
+    // by color, start and end square, how much cutoffs a quiet move caused anywhere in the tree
+    history: Vec<i32>,
+}
+
+impl SearchState {
+    pub fn pv(&self, ply: usize) -> &[PieceMove] {
+        self.pv_lines.get(ply).map_or(&[], |pv| pv.as_slice())
+    }
+    pub fn clear_pv(&mut self, ply: usize) {
+        if let Some(pv) = self.pv_lines.get_mut(ply) {
+            pv.clear();
+        }
+    }
+    // piece_move raised alpha at ply, so the line there is it followed by the line one ply down
+    pub fn update_pv(&mut self, ply: usize, piece_move: PieceMove) {
+        if self.pv_lines.len() <= ply + 1 {
+            self.pv_lines.resize(ply + 2, Vec::new());
+        }
+        let (lines, child_lines) = self.pv_lines.split_at_mut(ply + 1);
+        let pv = &mut lines[ply];
+        pv.clear();
+        pv.push(piece_move);
+        pv.extend_from_slice(&child_lines[0]);
+    }
+    pub fn killers(&self, ply: usize) -> [Option<PieceMove>; 2] {
+        self.killers.get(ply).copied().unwrap_or_default()
+    }
+    pub fn history(&self, color: Color, piece_move: &PieceMove) -> i32 {
+        self.history
+            .get(history_idx(color, piece_move))
+            .copied()
+            .unwrap_or(0)
+    }
+    // a quiet move caused a beta cutoff, deeper cutoffs count for more
+    pub fn record_cutoff(&mut self, color: Color, ply: usize, piece_move: PieceMove, depth: u8) {
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; 2]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(piece_move) {
+            killers[1] = killers[0];
+            killers[0] = Some(piece_move);
+        }
+        if self.history.is_empty() {
+            self.history = vec![0; 2 * 64 * 64];
+        }
+        let history = &mut self.history[history_idx(color, &piece_move)];
+        *history = (*history + depth as i32 * depth as i32).min(MAX_HISTORY);
+    }
+}
+
+fn history_idx(color: Color, piece_move: &PieceMove) -> usize {
+    (color.index() * 64 + piece_move.start.index()) * 64 + piece_move.end.index()
+}
@@ -1,3 +1,5 @@
+use crate::piece::Piece;
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum SpecialMove {
     KnightPromotion,
@@ -9,3 +11,15 @@ pub enum SpecialMove {
     CastleQueenside,
     None,
 }
+
+impl SpecialMove {
+    pub fn promotion_piece(self) -> Option<Piece> {
+        match self {
+            SpecialMove::KnightPromotion => Some(Piece::Knight),
+            SpecialMove::BishopPromotion => Some(Piece::Bishop),
+            SpecialMove::RookPromotion => Some(Piece::Rook),
+            SpecialMove::QueenPromotion => Some(Piece::Queen),
+            _ => None,
+        }
+    }
+}
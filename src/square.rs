@@ -0,0 +1,170 @@
+use crate::parseerror::ParseError;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+// 0 is a1, 7 is h1, 63 is h8
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct Square(u8);
+
+impl Square {
+    pub const A1: Square = Square(0);
+    pub const B1: Square = Square(1);
+    pub const C1: Square = Square(2);
+    pub const D1: Square = Square(3);
+    pub const E1: Square = Square(4);
+    pub const F1: Square = Square(5);
+    pub const G1: Square = Square(6);
+    pub const H1: Square = Square(7);
+    pub const A2: Square = Square(8);
+    pub const B2: Square = Square(9);
+    pub const C2: Square = Square(10);
+    pub const D2: Square = Square(11);
+    pub const E2: Square = Square(12);
+    pub const F2: Square = Square(13);
+    pub const G2: Square = Square(14);
+    pub const H2: Square = Square(15);
+    pub const A3: Square = Square(16);
+    pub const B3: Square = Square(17);
+    pub const C3: Square = Square(18);
+    pub const D3: Square = Square(19);
+    pub const E3: Square = Square(20);
+    pub const F3: Square = Square(21);
+    pub const G3: Square = Square(22);
+    pub const H3: Square = Square(23);
+    pub const A4: Square = Square(24);
+    pub const B4: Square = Square(25);
+    pub const C4: Square = Square(26);
+    pub const D4: Square = Square(27);
+    pub const E4: Square = Square(28);
+    pub const F4: Square = Square(29);
+    pub const G4: Square = Square(30);
+    pub const H4: Square = Square(31);
+    pub const A5: Square = Square(32);
+    pub const B5: Square = Square(33);
+    pub const C5: Square = Square(34);
+    pub const D5: Square = Square(35);
+    pub const E5: Square = Square(36);
+    pub const F5: Square = Square(37);
+    pub const G5: Square = Square(38);
+    pub const H5: Square = Square(39);
+    pub const A6: Square = Square(40);
+    pub const B6: Square = Square(41);
+    pub const C6: Square = Square(42);
+    pub const D6: Square = Square(43);
+    pub const E6: Square = Square(44);
+    pub const F6: Square = Square(45);
+    pub const G6: Square = Square(46);
+    pub const H6: Square = Square(47);
+    pub const A7: Square = Square(48);
+    pub const B7: Square = Square(49);
+    pub const C7: Square = Square(50);
+    pub const D7: Square = Square(51);
+    pub const E7: Square = Square(52);
+    pub const F7: Square = Square(53);
+    pub const G7: Square = Square(54);
+    pub const H7: Square = Square(55);
+    pub const A8: Square = Square(56);
+    pub const B8: Square = Square(57);
+    pub const C8: Square = Square(58);
+    pub const D8: Square = Square(59);
+    pub const E8: Square = Square(60);
+    pub const F8: Square = Square(61);
+    pub const G8: Square = Square(62);
+    pub const H8: Square = Square(63);
+
+    pub fn new(index: u8) -> Square {
+        assert!(index < 64, "square index {} out of range", index);
+        Square(index)
+    }
+    // file and rank both 0..8
+    pub fn from_coords(file: u8, rank: u8) -> Square {
+        assert!(
+            file < 8 && rank < 8,
+            "square ({}, {}) out of range",
+            file,
+            rank
+        );
+        Square(rank * 8 + file)
+    }
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+    pub fn file(self) -> u8 {
+        self.0 % 8
+    }
+    pub fn rank(self) -> u8 {
+        self.0 / 8
+    }
+    pub fn bitboard(self) -> u64 {
+        1u64 << self.0
+    }
+    pub fn all() -> impl Iterator<Item = Square> {
+        (0..64).map(Square)
+    }
+}
+
+impl From<Square> for u8 {
+    fn from(square: Square) -> u8 {
+        square.0
+    }
+}
+
+impl TryFrom<u8> for Square {
+    type Error = ParseError;
+    fn try_from(index: u8) -> Result<Square, ParseError> {
+        if index < 64 {
+            Ok(Square(index))
+        } else {
+            Err(ParseError::BadSquare(index.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.file()) as char, self.rank() + 1)
+    }
+}
+
+impl FromStr for Square {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Square, ParseError> {
+        match s.as_bytes() {
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => {
+                Ok(Square::from_coords(file - b'a', rank - b'1'))
+            }
+            _ => Err(ParseError::BadSquare(s.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parseerror::ParseError;
+    use crate::square::Square;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_square() {
+        assert_eq!(Square::E4.index(), 28);
+        assert_eq!(Square::H8, Square::new(63));
+        assert_eq!(Square::from_coords(4, 3), Square::E4);
+        assert_eq!((Square::C7.file(), Square::C7.rank()), (2, 6));
+        assert_eq!("e4".parse(), Ok(Square::E4));
+        assert_eq!(Square::A1.to_string(), "a1");
+        for square in Square::all() {
+            assert_eq!(square.to_string().parse(), Ok(square));
+        }
+        assert_eq!(
+            "i1".parse::<Square>(),
+            Err(ParseError::BadSquare("i1".to_owned()))
+        );
+        assert!("e".parse::<Square>().is_err());
+        assert!("e44".parse::<Square>().is_err());
+        assert_eq!(
+            Square::try_from(64),
+            Err(ParseError::BadSquare("64".to_owned()))
+        );
+    }
+}
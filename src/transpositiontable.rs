@@ -0,0 +1,115 @@
+use crate::bound::Bound;
+use crate::piecemove::PieceMove;
+use crate::score::Score;
+use crate::specialmove::SpecialMove;
+use crate::square::Square;
+use crate::ttentry::TtEntry;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+const SPECIAL_MOVES: [SpecialMove; 8] = [
+    SpecialMove::KnightPromotion,
+    SpecialMove::BishopPromotion,
+    SpecialMove::RookPromotion,
+    SpecialMove::QueenPromotion,
+    SpecialMove::EnPassant,
+    SpecialMove::CastleKingside,
+    SpecialMove::CastleQueenside,
+    SpecialMove::None,
+];
+
+// Shared between search threads without locking. Each slot holds the key xored with the data,
+// so a slot torn by two threads writing at once just fails the key check on probe.
+pub struct TranspositionTable {
+    slots: Vec<[AtomicU64; 2]>,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> TranspositionTable {
+        TranspositionTable::try_new(size_mb).expect("Failed to allocate transposition table")
+    }
+    // None if there isn't the memory for it
+    pub fn try_new(size_mb: usize) -> Option<TranspositionTable> {
+        let size = size_mb.saturating_mul(1024 * 1024);
+        let max_slots = (size / std::mem::size_of::<[AtomicU64; 2]>()).max(1);
+        // power of two so the index is just the low bits of the key
+        let slot_count = 1 << (63 - (max_slots as u64).leading_zeros());
+        let mut slots = Vec::new();
+        slots.try_reserve_exact(slot_count).ok()?;
+        for _ in 0..slot_count {
+            slots.push([AtomicU64::new(0), AtomicU64::new(0)]);
+        }
+        Some(TranspositionTable { slots })
+    }
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            slot[0].store(0, Ordering::Relaxed);
+            slot[1].store(0, Ordering::Relaxed);
+        }
+    }
+    pub fn probe(&self, hash: u64) -> Option<TtEntry> {
+        let slot = &self.slots[hash as usize & (self.slots.len() - 1)];
+        let checked_key = slot[0].load(Ordering::Relaxed);
+        let data = slot[1].load(Ordering::Relaxed);
+        if data == 0 || checked_key ^ data != hash {
+            return None;
+        }
+        Some(TranspositionTable::unpack(data))
+    }
+    pub fn store(&self, hash: u64, entry: TtEntry) {
+        let slot = &self.slots[hash as usize & (self.slots.len() - 1)];
+        let data = slot[1].load(Ordering::Relaxed);
+        // keep deeper results for the same position, anything else gets replaced
+        if data != 0
+            && slot[0].load(Ordering::Relaxed) ^ data == hash
+            && TranspositionTable::unpack(data).depth > entry.depth
+        {
+            return;
+        }
+        let data = TranspositionTable::pack(&entry);
+        slot[0].store(hash ^ data, Ordering::Relaxed);
+        slot[1].store(data, Ordering::Relaxed);
+    }
+    // score bits | depth << 32 | bound << 40 | move << 42, with bit 63 set so data is never 0
+    fn pack(entry: &TtEntry) -> u64 {
+        let mut data = entry.score.0 as u32 as u64;
+        data |= (entry.depth as u64) << 32;
+        data |= match entry.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        } << 40;
+        if let Some(best_move) = entry.best_move {
+            let special = SPECIAL_MOVES
+                .iter()
+                .position(|special| *special == best_move.special)
+                .unwrap_or(7) as u64;
+            data |= 1 << 42;
+            data |= (best_move.start.index() as u64) << 43;
+            data |= (best_move.end.index() as u64) << 49;
+            data |= special << 55;
+        }
+        data | 1 << 63
+    }
+    fn unpack(data: u64) -> TtEntry {
+        let bound = match (data >> 40) & 3 {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            _ => Bound::Upper,
+        };
+        let best_move = if (data >> 42) & 1 != 0 {
+            Some(PieceMove {
+                start: Square::new(((data >> 43) & 63) as u8),
+                end: Square::new(((data >> 49) & 63) as u8),
+                special: SPECIAL_MOVES[((data >> 55) & 7) as usize],
+            })
+        } else {
+            None
+        };
+        TtEntry {
+            depth: ((data >> 32) & 255) as u8,
+            bound,
+            score: Score(data as u32 as i32),
+            best_move,
+        }
+    }
+}
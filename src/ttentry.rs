@@ -0,0 +1,12 @@
+use crate::bound::Bound;
+use crate::piecemove::PieceMove;
+use crate::score::Score;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TtEntry {
+    pub depth: u8,
+    pub bound: Bound,
+    // mates are counted from the stored position, not the root
+    pub score: Score,
+    pub best_move: Option<PieceMove>,
+}
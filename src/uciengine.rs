@@ -0,0 +1,345 @@
+use crate::engineoptions::EngineOptions;
+use crate::game::Game;
+use crate::searchhandle::SearchHandle;
+use crate::searchlimits::SearchLimits;
+use crate::transpositiontable::TranspositionTable;
+use crate::ucierror::UciError;
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+const COMMANDS: [&str; 11] = [
+    "uci",
+    "debug",
+    "isready",
+    "setoption",
+    "register",
+    "ucinewgame",
+    "position",
+    "go",
+    "stop",
+    "ponderhit",
+    "quit",
+];
+const GO_TOKENS: [&str; 12] = [
+    "searchmoves",
+    "ponder",
+    "wtime",
+    "btime",
+    "winc",
+    "binc",
+    "movestogo",
+    "depth",
+    "nodes",
+    "mate",
+    "movetime",
+    "infinite",
+];
+
+// The UCI protocol over any input and output. Search threads write info and bestmove lines to
+// the same output, so it sits behind a mutex.
+pub struct UciEngine<W: Write + Send + 'static> {
+    output: Arc<Mutex<W>>,
+    game: Game,
+    options: EngineOptions,
+    tt: Arc<TranspositionTable>,
+    debug: bool,
+    search: Option<SearchHandle>,
+}
+
+impl<W: Write + Send + 'static> UciEngine<W> {
+    pub fn new(output: W) -> UciEngine<W> {
+        let mut game = Game::new();
+        game.starting_game();
+        let options = EngineOptions::default();
+        let tt = Arc::new(TranspositionTable::new(options.hash_mb));
+        UciEngine {
+            output: Arc::new(Mutex::new(output)),
+            game,
+            options,
+            tt,
+            debug: false,
+            search: None,
+        }
+    }
+    // Handles commands until quit or the end of input. At the end of input a running search
+    // still finishes and reports, unless it would only end with stop.
+    pub fn run<R: BufRead>(&mut self, input: R) {
+        for line in input.lines() {
+            // input is gone or unreadable, so no more commands are coming
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            if !self.handle_line(&line) {
+                self.stop_search();
+                return;
+            }
+        }
+        if let Some(search) = self.search.take() {
+            if search.waits_for_stop() {
+                search.stop();
+            }
+            search.join();
+        }
+    }
+    // false once the line was quit
+    pub fn handle_line(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        // UCI says to skip unknown tokens and look for a command in the rest of the line
+        let command_idx = match tokens.iter().position(|token| COMMANDS.contains(token)) {
+            Some(command_idx) => command_idx,
+            None => {
+                if let Some(token) = tokens.first() {
+                    self.report(UciError::UnknownCommand(token.to_string()));
+                }
+                return true;
+            }
+        };
+        if command_idx > 0 {
+            self.report(UciError::UnknownCommand(tokens[0].to_owned()));
+        }
+        let args = &tokens[command_idx + 1..];
+        match tokens[command_idx] {
+            "quit" => return false,
+            "uci" => {
+                self.send("id name Plankton Engine");
+                self.send("id author Nosrep");
+                for option in self.options.uci_options() {
+                    self.send(option);
+                }
+                self.send("uciok");
+            }
+            "debug" => match args.first() {
+                Some(&"on") => self.debug = true,
+                Some(&"off") => self.debug = false,
+                Some(value) => self.report(UciError::BadValue {
+                    name: "debug".to_owned(),
+                    value: value.to_string(),
+                }),
+                None => self.report(UciError::MissingValue("debug".to_owned())),
+            },
+            "isready" => self.send("readyok"),
+            "setoption" => {
+                let hash_mb = self.options.hash_mb;
+                let options = &mut self.options;
+                let set_option = parse_setoption(args).and_then(|(name, value)| {
+                    options.set(&name, &value).map_err(UciError::BadOption)
+                });
+                match set_option {
+                    Ok(()) if self.options.hash_mb != hash_mb => {
+                        match TranspositionTable::try_new(self.options.hash_mb) {
+                            Some(tt) => self.tt = Arc::new(tt),
+                            None => {
+                                self.report(UciError::HashAllocation(self.options.hash_mb));
+                                self.options.hash_mb = hash_mb;
+                            }
+                        }
+                    }
+                    Ok(()) => (),
+                    Err(err) => self.report(err),
+                }
+            }
+            // nothing to register
+            "register" => (),
+            "ucinewgame" => {
+                self.game.starting_game();
+                self.tt.clear();
+            }
+            // an illegal move anywhere rejects the whole command, so game is never half set up
+            "position" => match parse_position(args) {
+                Ok(position) => {
+                    self.game = position;
+                    if self.debug {
+                        self.send(format_args!("info string position {}", self.game.to_fen()));
+                    }
+                }
+                Err(err) => self.report(err),
+            },
+            "go" => {
+                // the GUI waits for a bestmove, so search with whatever did parse
+                let (limits, errors) = parse_go(args, &mut self.game);
+                for err in errors {
+                    self.report(err);
+                }
+                // a go while searching replaces the old search, which still gets its bestmove out
+                self.stop_search();
+                let start_time = Instant::now();
+                let debug = self.debug;
+                let info_output = Arc::clone(&self.output);
+                let bestmove_output = Arc::clone(&self.output);
+                self.search = Some(SearchHandle::spawn(
+                    self.game.clone(),
+                    limits,
+                    &self.options,
+                    Arc::clone(&self.tt),
+                    move |info| send(&info_output, info),
+                    move |best_move| {
+                        match best_move {
+                            Some(result) => send(
+                                &bestmove_output,
+                                format_args!("bestmove {}", result.best_move),
+                            ),
+                            // no legal moves, the null move tells the GUI we know
+                            None => send(&bestmove_output, "bestmove 0000"),
+                        }
+                        if debug {
+                            let time = start_time.elapsed().as_millis();
+                            send(&bestmove_output, format_args!("info time {}", time));
+                        }
+                    },
+                ));
+            }
+            "stop" => {
+                if let Some(search) = &self.search {
+                    search.stop();
+                }
+            }
+            "ponderhit" => {
+                if let Some(search) = &self.search {
+                    search.ponderhit();
+                }
+            }
+            _ => (),
+        }
+        true
+    }
+    // stops the running search, if any, and waits for its bestmove
+    pub fn stop_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.stop();
+            search.join();
+        }
+    }
+    fn send(&self, line: impl Display) {
+        send(&self.output, line);
+    }
+    fn report(&self, err: UciError) {
+        self.send(format_args!("info string {}", err));
+    }
+}
+
+// a closed output can't be reported anywhere, so write errors are dropped
+fn send<W: Write>(output: &Mutex<W>, line: impl Display) {
+    if let Ok(mut output) = output.lock() {
+        let _ = writeln!(output, "{}", line);
+        let _ = output.flush();
+    }
+}
+
+// setoption name <name> [value <value>], both of which can have spaces
+fn parse_setoption(args: &[&str]) -> Result<(String, String), UciError> {
+    let value_idx = args
+        .iter()
+        .position(|token| *token == "value")
+        .unwrap_or(args.len());
+    if args.first() != Some(&"name") || value_idx < 2 {
+        return Err(UciError::MissingValue("setoption name".to_owned()));
+    }
+    let name = args[1..value_idx].join(" ");
+    let value = args.get(value_idx + 1..).unwrap_or_default().join(" ");
+    Ok((name, value))
+}
+
+// position [startpos | fen <fen>] [moves <move>...]
+fn parse_position(args: &[&str]) -> Result<Game, UciError> {
+    let moves_idx = args
+        .iter()
+        .position(|token| *token == "moves")
+        .unwrap_or(args.len());
+    let mut game = match args.first() {
+        Some(&"startpos") => {
+            if moves_idx > 1 {
+                return Err(UciError::UnknownToken {
+                    command: "position".to_owned(),
+                    token: args[1].to_owned(),
+                });
+            }
+            let mut game = Game::new();
+            game.starting_game();
+            game
+        }
+        Some(&"fen") => Game::from_fen(&args[1..moves_idx].join(" ")).map_err(UciError::BadFen)?,
+        Some(token) => {
+            return Err(UciError::UnknownToken {
+                command: "position".to_owned(),
+                token: token.to_string(),
+            })
+        }
+        None => return Err(UciError::MissingValue("position".to_owned())),
+    };
+    for move_str in args.get(moves_idx + 1..).unwrap_or_default() {
+        let piece_move = game.parse_uci_move(move_str).map_err(UciError::BadMove)?;
+        game.play_move(&piece_move);
+    }
+    Ok(game)
+}
+
+// every bad token is an error, the limits are whatever could be read around them
+fn parse_go(args: &[&str], game: &mut Game) -> (SearchLimits, Vec<UciError>) {
+    let mut limits = SearchLimits::default();
+    let mut errors = Vec::new();
+    let mut idx = 0;
+    while idx < args.len() {
+        let token = args[idx];
+        idx += 1;
+        match token {
+            "infinite" => limits.infinite = true,
+            "ponder" => limits.ponder = true,
+            // moves run until the next go token
+            "searchmoves" => {
+                while idx < args.len() && !GO_TOKENS.contains(&args[idx]) {
+                    match game.parse_uci_move(args[idx]) {
+                        Ok(piece_move) => limits.search_moves.push(piece_move),
+                        Err(err) => errors.push(UciError::BadMove(err)),
+                    }
+                    idx += 1;
+                }
+            }
+            _ if !GO_TOKENS.contains(&token) => errors.push(UciError::UnknownToken {
+                command: "go".to_owned(),
+                token: token.to_owned(),
+            }),
+            // the rest all take a number
+            name => {
+                let value = match args.get(idx) {
+                    Some(value) if !GO_TOKENS.contains(value) => value,
+                    _ => {
+                        errors.push(UciError::MissingValue(name.to_owned()));
+                        continue;
+                    }
+                };
+                idx += 1;
+                let parsed = match name {
+                    "wtime" => parse_value(name, value).map(|time| limits.times[0] = Some(time)),
+                    "btime" => parse_value(name, value).map(|time| limits.times[1] = Some(time)),
+                    "winc" => parse_value(name, value).map(|inc| limits.increments[0] = inc),
+                    "binc" => parse_value(name, value).map(|inc| limits.increments[1] = inc),
+                    "movestogo" => parse_value(name, value).map(|moves| {
+                        limits.moves_to_go = Some(moves);
+                    }),
+                    "movetime" => parse_value(name, value).map(|time| {
+                        limits.move_time = Some(time);
+                    }),
+                    "depth" => parse_value(name, value).map(|depth| limits.depth = Some(depth)),
+                    "nodes" => parse_value(name, value).map(|nodes| limits.nodes = Some(nodes)),
+                    "mate" => parse_value(name, value).map(|mate| limits.mate = Some(mate)),
+                    _ => Ok(()),
+                };
+                if let Err(err) = parsed {
+                    errors.push(err);
+                }
+            }
+        }
+    }
+    (limits, errors)
+}
+
+fn parse_value<T: FromStr>(name: &str, value: &str) -> Result<T, UciError> {
+    value.parse().map_err(|_| UciError::BadValue {
+        name: name.to_owned(),
+        value: value.to_owned(),
+    })
+}
@@ -0,0 +1,41 @@
+use crate::fenerror::FenError;
+use crate::moveerror::MoveError;
+use crate::optionerror::OptionError;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum UciError {
+    UnknownCommand(String),
+    UnknownToken { command: String, token: String },
+    MissingValue(String),
+    BadValue { name: String, value: String },
+    BadFen(FenError),
+    BadMove(MoveError),
+    BadOption(OptionError),
+    // the new hash size couldn't be allocated, so the old table stays
+    HashAllocation(usize),
+}
+
+impl fmt::Display for UciError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UciError::UnknownCommand(command) => write!(f, "unknown command \"{}\"", command),
+            UciError::UnknownToken { command, token } => {
+                write!(f, "unknown token \"{}\" in {}", token, command)
+            }
+            UciError::MissingValue(name) => write!(f, "missing value for {}", name),
+            UciError::BadValue { name, value } => {
+                write!(f, "\"{}\" is not a valid value for {}", value, name)
+            }
+            UciError::BadFen(err) => write!(f, "bad fen: {}", err),
+            UciError::BadMove(err) => write!(f, "{}", err),
+            UciError::BadOption(err) => write!(f, "{}", err),
+            UciError::HashAllocation(hash_mb) => {
+                write!(f, "not enough memory for a {} MB hash table", hash_mb)
+            }
+        }
+    }
+}
+
+impl Error for UciError {}
@@ -0,0 +1,130 @@
+use crate::game::Game;
+use crate::moveerror::MoveError;
+use crate::piece::Piece;
+use crate::piecemove::PieceMove;
+use crate::square::Square;
+use std::fmt;
+
+impl PieceMove {
+    // long algebraic notation as UCI uses it, e.g. e2e4, e7e8q, castling as the king's move
+    pub fn to_uci(&self) -> String {
+        match self.special.promotion_piece() {
+            Some(promotion) => format!(
+                "{}{}{}",
+                self.start,
+                self.end,
+                promotion.to_char().to_ascii_lowercase()
+            ),
+            None => format!("{}{}", self.start, self.end),
+        }
+    }
+}
+
+impl fmt::Display for PieceMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_uci())
+    }
+}
+
+impl Game {
+    // the flags come from the matching legal move, so castling and en passant don't need guessing
+    pub fn parse_uci_move(&mut self, uci_move: &str) -> Result<PieceMove, MoveError> {
+        let bad_format = || MoveError::BadFormat(uci_move.to_owned());
+        if !uci_move.is_ascii() || !(4..=5).contains(&uci_move.len()) {
+            return Err(bad_format());
+        }
+        let start: Square = uci_move[0..2].parse().map_err(|_| bad_format())?;
+        let end: Square = uci_move[2..4].parse().map_err(|_| bad_format())?;
+        let promotion = match uci_move[4..].chars().next() {
+            Some(promotion_char @ ('n' | 'b' | 'r' | 'q')) => Piece::from_char(promotion_char),
+            Some(_) => return Err(bad_format()),
+            None => None,
+        };
+        self.legal_moves()
+            .into_iter()
+            .find(|piece_move| {
+                piece_move.start == start
+                    && piece_move.end == end
+                    && piece_move.special.promotion_piece() == promotion
+            })
+            .ok_or_else(|| MoveError::Illegal(uci_move.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::Game;
+    use crate::moveerror::MoveError;
+    use crate::piecemove::PieceMove;
+    use crate::specialmove::SpecialMove;
+    use crate::square::Square;
+
+    #[test]
+    fn test_to_uci() {
+        let piece_move = PieceMove {
+            start: Square::E2,
+            end: Square::E4,
+            special: SpecialMove::None,
+        };
+        assert_eq!(piece_move.to_uci(), "e2e4");
+        let promotion = PieceMove {
+            start: Square::B7,
+            end: Square::A8,
+            special: SpecialMove::KnightPromotion,
+        };
+        assert_eq!(promotion.to_string(), "b7a8n");
+    }
+
+    #[test]
+    fn test_parse_uci_move() {
+        let mut game =
+            Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        assert_eq!(
+            game.parse_uci_move("e1g1").unwrap().special,
+            SpecialMove::CastleKingside
+        );
+        assert_eq!(
+            game.parse_uci_move("e1c1").unwrap().special,
+            SpecialMove::CastleQueenside
+        );
+        assert_eq!(
+            game.parse_uci_move("e2a6").unwrap(),
+            PieceMove {
+                start: Square::E2,
+                end: Square::A6,
+                special: SpecialMove::None,
+            }
+        );
+        let double_push = game.parse_uci_move("a2a4").unwrap();
+        game.play_move(&double_push);
+        assert_eq!(
+            game.parse_uci_move("b4a3").unwrap().special,
+            SpecialMove::EnPassant
+        );
+        for uci_move in &["", "e2", "e2e4e", "e2e9", "i2i4", "a7a8k", "e2e4 "] {
+            assert_eq!(
+                game.parse_uci_move(uci_move),
+                Err(MoveError::BadFormat(uci_move.to_string()))
+            );
+        }
+        // white's move, and a pawn can't go three squares
+        assert_eq!(
+            game.parse_uci_move("e2e4"),
+            Err(MoveError::Illegal("e2e4".to_owned()))
+        );
+        assert!(game.parse_uci_move("c7c4").is_err());
+
+        let mut game = Game::from_fen("8/2P5/8/8/8/8/8/k1K5 w - - 0 1").unwrap();
+        assert_eq!(
+            game.parse_uci_move("c7c8n").unwrap().special,
+            SpecialMove::KnightPromotion
+        );
+        assert_eq!(
+            game.parse_uci_move("c7c8q").unwrap().special,
+            SpecialMove::QueenPromotion
+        );
+        // a pawn reaching the last rank has to say what it becomes
+        assert!(game.parse_uci_move("c7c8").is_err());
+    }
+}
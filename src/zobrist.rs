@@ -0,0 +1,41 @@
+use crate::color::Color;
+use crate::piece::Piece;
+use crate::square::Square;
+
+// piece keys first (color, piece, square), then castling rights, en passant files and side to move
+const KEY_COUNT: usize = 2 * 6 * 64 + 4 + 8 + 1;
+const CASTLE_OFFSET: usize = 2 * 6 * 64;
+const EN_PASSANT_OFFSET: usize = CASTLE_OFFSET + 4;
+const SIDE_OFFSET: usize = EN_PASSANT_OFFSET + 8;
+
+// fixed seed so keys are the same for every game and every run
+const KEYS: [u64; KEY_COUNT] = {
+    let mut keys = [0; KEY_COUNT];
+    let mut state: u64 = 0x9e3779b97f4a7c15;
+    let mut idx = 0;
+    while idx < KEY_COUNT {
+        // xorshift64*
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        keys[idx] = state.wrapping_mul(0x2545f4914f6cdd1d);
+        idx += 1;
+    }
+    keys
+};
+
+pub fn piece_key(color: Color, piece: Piece, square: Square) -> u64 {
+    KEYS[(color as usize * 6 + piece as usize) * 64 + square.index()]
+}
+
+pub fn castle_key(castle_idx: usize) -> u64 {
+    KEYS[CASTLE_OFFSET + castle_idx]
+}
+
+pub fn en_passant_key(square: Square) -> u64 {
+    KEYS[EN_PASSANT_OFFSET + square.file() as usize]
+}
+
+pub fn side_key() -> u64 {
+    KEYS[SIDE_OFFSET]
+}
@@ -0,0 +1,206 @@
+use plankton::game::Game;
+use plankton::perft;
+
+fn setup(fen: &str) -> Game {
+    Game::from_fen(fen).unwrap()
+}
+
+fn check_perft(fen: &str, expected: &[u64]) {
+    let mut game = setup(fen);
+    assert_eq!(game.to_fen(), fen);
+    let before = game.clone();
+    for (depth, expected_nodes) in expected.iter().enumerate() {
+        assert_eq!(
+            perft::perft(&mut game, depth as u8 + 1),
+            *expected_nodes,
+            "{} at depth {}",
+            fen,
+            depth + 1
+        );
+    }
+    assert!(game == before, "{} not restored after perft", fen);
+}
+
+#[test]
+fn test_perft_start() {
+    check_perft(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        &[20, 400, 8902],
+    );
+}
+
+#[test]
+fn test_perft_kiwipete() {
+    check_perft(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        &[48, 2039],
+    );
+}
+
+#[test]
+fn test_perft_position_3() {
+    check_perft(
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        &[14, 191, 2812],
+    );
+}
+
+#[test]
+fn test_perft_position_4() {
+    check_perft(
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        &[6, 264, 9467],
+    );
+    check_perft(
+        "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+        &[6, 264, 9467],
+    );
+}
+
+#[test]
+fn test_perft_position_5() {
+    check_perft(
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        &[44, 1486],
+    );
+}
+
+#[test]
+fn test_perft_en_passant() {
+    // en passant capture that would leave the king in check
+    check_perft("3k4/3p4/8/K1P4r/8/8/8/8 b - - 0 1", &[18, 92, 1670, 10138]);
+    // en passant capture that gives check
+    check_perft(
+        "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1",
+        &[15, 126, 1928, 13931],
+    );
+    check_perft(
+        "8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1",
+        &[13, 102, 1266, 10276],
+    );
+}
+
+#[test]
+fn test_perft_castling() {
+    // castling that gives check
+    check_perft("5k2/8/8/8/8/8/8/4K2R w K - 0 1", &[15, 66, 1198, 6399]);
+    check_perft("3k4/8/8/8/8/8/8/R3K3 w Q - 0 1", &[16, 71, 1286, 7418]);
+    // castling rights lost when the rooks are captured
+    check_perft("r3k2r/1b4bq/8/8/8/8/7B/R3K2R w KQkq - 0 1", &[26, 1141]);
+    // castling out of and through check
+    check_perft("r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1", &[44, 1494]);
+}
+
+#[test]
+fn test_perft_promotion() {
+    // promoting out of check
+    check_perft("2K2r2/4P3/8/8/8/8/8/3k4 w - - 0 1", &[11, 133, 1442, 19174]);
+    // promoting and underpromoting to give check
+    check_perft("4k3/1P6/8/8/8/8/K7/8 w - - 0 1", &[9, 40, 472, 2661]);
+    check_perft("8/P1k5/K7/8/8/8/8/8 w - - 0 1", &[6, 27, 273, 1329]);
+    // stalemate and checkmate right after promoting
+    check_perft("K1k5/8/P7/8/8/8/8/8 w - - 0 1", &[2, 6, 13, 63]);
+    check_perft("8/k1P5/8/1K6/8/8/8/8 w - - 0 1", &[10, 25, 268, 926]);
+}
+
+#[test]
+fn test_perft_checks() {
+    // discovered check
+    check_perft("8/8/1P2K3/8/2n5/1q6/8/5k2 b - - 0 1", &[29, 165, 5160]);
+    // double check
+    check_perft("8/8/2k5/5q2/5n2/8/5K2/8 b - - 0 1", &[37, 183, 6559]);
+}
+
+fn check_hash(game: &mut Game, depth: u8) {
+    assert_eq!(game.hash, game.compute_hash());
+    if depth == 0 {
+        return;
+    }
+    for piece_move in game.legal_moves() {
+        let prev_game_state = game.play_move(&piece_move);
+        check_hash(game, depth - 1);
+        game.undo_move(&piece_move, &prev_game_state);
+    }
+}
+
+#[test]
+fn test_incremental_hash() {
+    for fen in &[
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1",
+    ] {
+        let mut game = setup(fen);
+        check_hash(&mut game, 2);
+    }
+}
+
+fn check_fen(game: &mut Game, depth: u8) {
+    let fen = game.to_fen();
+    let parsed = Game::from_fen(&fen).unwrap();
+    assert_eq!(parsed.to_fen(), fen);
+    assert_eq!(parsed.hash, game.hash, "{}", fen);
+    if depth == 0 {
+        return;
+    }
+    for piece_move in game.legal_moves() {
+        let prev_game_state = game.play_move(&piece_move);
+        check_fen(game, depth - 1);
+        game.undo_move(&piece_move, &prev_game_state);
+    }
+}
+
+#[test]
+fn test_fen_round_trip() {
+    for fen in &[
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        "8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1",
+    ] {
+        let mut game = setup(fen);
+        check_fen(&mut game, 1);
+    }
+}
+
+#[test]
+fn test_divide() {
+    let mut game = setup("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    let divided = perft::divide(&mut game, 2);
+    assert_eq!(divided.len(), 48);
+    assert_eq!(divided.iter().map(|(_, nodes)| nodes).sum::<u64>(), 2039);
+}
+
+// the full reference depths take minutes without optimizations, run them with
+// `cargo test --release -- --ignored`
+#[test]
+#[ignore]
+fn test_perft_deep() {
+    check_perft(
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        &[20, 400, 8902, 197281, 4865609],
+    );
+    check_perft(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        &[48, 2039, 97862, 4085603],
+    );
+    check_perft(
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        &[14, 191, 2812, 43238, 674624],
+    );
+    check_perft(
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        &[6, 264, 9467, 422333],
+    );
+    check_perft(
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        &[44, 1486, 62379, 2103487],
+    );
+    check_perft(
+        "r3k2r/1b4bq/8/8/8/8/7B/R3K2R w KQkq - 0 1",
+        &[26, 1141, 27826, 1274206],
+    );
+    check_perft(
+        "r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1",
+        &[44, 1494, 50509, 1720476],
+    );
+}
@@ -0,0 +1,134 @@
+use plankton::game::Game;
+use plankton::uciengine::UciEngine;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+// output that the test can still read after the engine has taken it
+#[derive(Clone, Default)]
+struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn session(input: &str) -> Vec<String> {
+    let output = SharedOutput::default();
+    UciEngine::new(output.clone()).run(input.as_bytes());
+    let bytes = output.0.lock().unwrap().clone();
+    String::from_utf8(bytes)
+        .unwrap()
+        .lines()
+        .map(|line| line.to_owned())
+        .collect()
+}
+
+fn bestmoves(output: &[String]) -> Vec<&str> {
+    output
+        .iter()
+        .filter_map(|line| line.strip_prefix("bestmove "))
+        .collect()
+}
+
+#[test]
+fn test_handshake() {
+    let output = session("uci\nisready\n");
+    assert_eq!(output[0], "id name Plankton Engine");
+    assert!(output.contains(&"option name MultiPV type spin default 1 min 1 max 256".to_owned()));
+    assert_eq!(output[output.len() - 2..], ["uciok", "readyok"]);
+}
+
+#[test]
+fn test_go_depth() {
+    let output =
+        session("setoption name Threads value 1\nposition startpos moves e2e4\ngo depth 3\n");
+    assert!(output[0].starts_with("info depth 1 seldepth "));
+    assert!(output[2].starts_with("info depth 3 seldepth "));
+    assert!(output[2].contains(" score cp "));
+    let bestmoves = bestmoves(&output);
+    assert_eq!(bestmoves.len(), 1);
+    let mut game =
+        Game::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    assert!(game.parse_uci_move(bestmoves[0]).is_ok());
+    assert!(output[2].contains(&format!(" pv {}", bestmoves[0])));
+}
+
+#[test]
+fn test_go_mate() {
+    let output = session("position fen 6k1/5ppp/8/8/8/8/8/1K2R3 w - - 0 1\ngo mate 1\n");
+    assert!(output[0].contains(" score mate 1 "));
+    assert_eq!(bestmoves(&output), ["e1e8"]);
+}
+
+#[test]
+fn test_multi_pv() {
+    let output = session(
+        "setoption name MultiPV value 3\nposition fen 6k1/5ppp/8/8/8/8/8/1K2R3 w - - 0 1\n\
+         go depth 1\n",
+    );
+    assert_eq!(output.len(), 4);
+    for (line_idx, line) in output[..3].iter().enumerate() {
+        assert!(line.contains(&format!(" multipv {} ", line_idx + 1)));
+    }
+}
+
+#[test]
+fn test_go_without_limits() {
+    // with no clock for the side to move and nothing else to go by, the search still ends
+    for go in ["go", "go wtime 1000", "go depth abc"] {
+        let output = session(&format!(
+            "setoption name Threads value 1\nposition startpos moves e2e4\n{}\n",
+            go
+        ));
+        assert_eq!(bestmoves(&output).len(), 1);
+    }
+}
+
+#[test]
+fn test_stop() {
+    let output = session("go infinite\nstop\nisready\n");
+    assert_eq!(bestmoves(&output).len(), 1);
+    assert!(output.contains(&"readyok".to_owned()));
+    // quit stops the running search, which still reports its bestmove, and nothing comes after
+    let output = session("go infinite\nquit\ngo depth 1\n");
+    assert_eq!(bestmoves(&output).len(), 1);
+}
+
+#[test]
+fn test_bad_input() {
+    let output = session(
+        "\n\
+         debug\n\
+         xyzzy\n\
+         position\n\
+         position fen 8/8 w - - 0 1\n\
+         setoption name Hash value lots\n\
+         setoption name Nothing value 1\n\
+         position startpos moves e2e4 e7e5 e4e5\n\
+         go wtime x depth 1 frob\n",
+    );
+    assert_eq!(
+        output[..8],
+        [
+            "info string missing value for debug",
+            "info string unknown command \"xyzzy\"",
+            "info string missing value for position",
+            "info string bad fen: piece placement: 2 ranks instead of 8",
+            "info string \"lots\" is not a valid value for Hash",
+            "info string no option named \"Nothing\"",
+            "info string e4e5 is not legal in this position",
+            "info string \"x\" is not a valid value for wtime",
+        ]
+    );
+    assert_eq!(output[8], "info string unknown token \"frob\" in go");
+    // the rejected position left the start position in place, so white is to move
+    let mut game = Game::new();
+    game.starting_game();
+    let bestmoves = bestmoves(&output);
+    assert_eq!(bestmoves.len(), 1);
+    assert!(game.parse_uci_move(bestmoves[0]).is_ok());
+}